@@ -0,0 +1,47 @@
+#[cfg(not(bench))]
+compile_error!("benchmarks must be run as `RUSTFLAGS=\"--cfg bench\" cargo bench --all-features`");
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+extern crate lispizzle;
+
+use lispizzle::Interpreter;
+
+/// Counts from `start` to `start + 10000` with `inc`, like the loops in
+/// `examples/macros.zle`.
+fn count_from(start: &str) -> String {
+    format!(
+        "(let (x {start} end (+ {start} 10000)) (while (< x end) (inc x)) x)",
+        start = start
+    )
+}
+
+#[inline(never)]
+fn run(interp: &Interpreter, code: &str) {
+    black_box(interp.eval_str(code).unwrap());
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let interp = Interpreter::new();
+
+    {
+        let mut g = c.benchmark_group("count 10000");
+
+        // Both loops do the same work, the second one has counters too large
+        // for the `i64` fast path of the arithmetic builtins.
+        let small = count_from("0");
+        let big = count_from("(expt 2 64)");
+        g.bench_with_input("fixnum", &small, |b, code| b.iter(|| run(&interp, code)));
+        g.bench_with_input("bignum", &big, |b, code| b.iter(|| run(&interp, code)));
+    }
+
+    {
+        let mut g = c.benchmark_group("sum 10000");
+
+        let code = "(let (i 0 sum 0) (while (< i 10000) (set! sum (+ sum (* i i))) (inc i)) sum)";
+        g.bench_with_input("fixnum", &code, |b, code| b.iter(|| run(&interp, code)));
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -1,8 +1,8 @@
-use std::fmt;
+use std::{fmt, rc::Rc};
 
 use im_rc::{vector, vector::ConsumingIter, Vector};
 
-use crate::{Error, Str, Symbol, Value};
+use crate::{parser::SourceSpan, Error, Str, Symbol, Value};
 
 #[derive(Clone)]
 enum TraceFrameRepr {
@@ -12,24 +12,44 @@ enum TraceFrameRepr {
 }
 
 #[derive(Clone)]
-pub struct TraceFrame(TraceFrameRepr);
+pub struct TraceFrame {
+    repr: TraceFrameRepr,
+    span: Option<Rc<SourceSpan>>,
+}
 
 #[derive(Clone)]
 pub struct BackTrace(Vector<TraceFrame>);
 
 impl TraceFrame {
     pub const fn main() -> Self {
-        Self(TraceFrameRepr::Main)
+        Self::new(TraceFrameRepr::Main)
     }
 
     #[inline]
     pub const fn unnamed(address: usize) -> Self {
-        Self(TraceFrameRepr::Unnamed(address))
+        Self::new(TraceFrameRepr::Unnamed(address))
     }
 
     #[inline]
     pub const fn named(address: usize, name: Symbol) -> Self {
-        Self(TraceFrameRepr::Named(address, name))
+        Self::new(TraceFrameRepr::Named(address, name))
+    }
+
+    #[inline]
+    const fn new(repr: TraceFrameRepr) -> Self {
+        Self { repr, span: None }
+    }
+
+    /// The position of the form being evaluated in this frame, if known.
+    #[inline]
+    pub fn span(&self) -> Option<Rc<SourceSpan>> {
+        self.span.clone()
+    }
+
+    #[inline]
+    pub fn with_span(mut self, span: Option<Rc<SourceSpan>>) -> Self {
+        self.span = span;
+        self
     }
 }
 
@@ -51,7 +71,7 @@ impl Eq for TraceFrameRepr {}
 impl PartialEq for TraceFrame {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.0.eq(&other.0)
+        self.repr.eq(&other.repr)
     }
 }
 
@@ -66,9 +86,14 @@ impl fmt::Display for TraceFrameRepr {
 }
 
 impl fmt::Display for TraceFrame {
-    #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&self.0, f)
+        fmt::Display::fmt(&self.repr, f)?;
+
+        if let Some(ref span) = self.span {
+            write!(f, " ({})", span)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -84,7 +109,7 @@ impl fmt::Debug for TraceFrameRepr {
 
 impl fmt::Debug for TraceFrame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "#<frame {}>", self.0)
+        write!(f, "#<frame {}>", self.repr)
     }
 }
 
@@ -120,6 +145,15 @@ impl BackTrace {
         Self(v)
     }
 
+    /// Records `span` as the position reached in the current frame.
+    pub fn with_span(&self, span: Option<Rc<SourceSpan>>) -> Self {
+        let mut v = self.0.clone();
+        if let Some(frame) = v.back_mut() {
+            frame.span = span;
+        }
+        Self(v)
+    }
+
     pub fn get(&self, i: usize) -> Option<TraceFrame> {
         self.0
             .len()
@@ -1,14 +1,20 @@
 use std::{borrow::Borrow, cell::RefCell, rc::Rc};
 
-use crate::{BackTrace, Str, StrCache, Symbol, TraceFrame};
+use crate::{parser::SourceSpan, BackTrace, RandomState, Str, StrCache, Symbol, TraceFrame};
 
 pub struct Context {
     cache: StrCache,
     trace: BackTrace,
+    span: Option<Rc<SourceSpan>>,
     gensym: Rc<RefCell<usize>>,
+    random: RandomState,
+    precision: u32,
 }
 
 impl Context {
+    /// The precision in bits of big floats computed by a new context.
+    pub const DEFAULT_PRECISION: u32 = 128;
+
     #[inline]
     pub fn new() -> Self {
         Self::with_cache(StrCache::new())
@@ -19,20 +25,47 @@ impl Context {
         Self {
             cache,
             trace: BackTrace::new(),
+            span: None,
             gensym: Rc::new(RefCell::new(0)),
+            random: RandomState::new(),
+            precision: Self::DEFAULT_PRECISION,
         }
     }
 
-    #[inline]
     pub fn trace(&self) -> BackTrace {
-        self.trace.clone()
+        if self.span.is_some() {
+            self.trace.with_span(self.span.clone())
+        } else {
+            self.trace.clone()
+        }
+    }
+
+    #[inline]
+    pub fn span(&self) -> Option<Rc<SourceSpan>> {
+        self.span.clone()
     }
 
     pub fn with_frame(&self, frame: TraceFrame) -> Self {
         Self {
             cache: self.cache.clone(),
-            trace: self.trace.with_frame(frame),
+            trace: self.trace().with_frame(frame),
+            span: None,
+            gensym: Rc::clone(&self.gensym),
+            random: self.random.clone(),
+            precision: self.precision,
+        }
+    }
+
+    /// Returns a context evaluating the form found at `span` in the current
+    /// frame.
+    pub fn with_span(&self, span: Rc<SourceSpan>) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            trace: self.trace.clone(),
+            span: Some(span),
             gensym: Rc::clone(&self.gensym),
+            random: self.random.clone(),
+            precision: self.precision,
         }
     }
 
@@ -41,6 +74,37 @@ impl Context {
         self.cache.get(s)
     }
 
+    /// The random number generator shared by this context and the contexts
+    /// derived from it.
+    #[inline]
+    pub fn random(&self) -> &RandomState {
+        &self.random
+    }
+
+    /// The precision in bits of the big floats computed in this context.
+    #[inline]
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+
+    /// Returns a context computing big floats with `precision` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `precision` is not between [`rug::float::prec_min`] and
+    /// [`rug::float::prec_max`].
+    pub fn with_precision(&self, precision: u32) -> Self {
+        assert!(
+            (rug::float::prec_min()..=rug::float::prec_max()).contains(&precision),
+            "precision out of range"
+        );
+
+        Self {
+            precision,
+            ..self.clone()
+        }
+    }
+
     pub fn make_sym(&self) -> Symbol {
         let mut gensym = RefCell::borrow_mut(&*self.gensym);
         let res = *gensym;
@@ -61,7 +125,10 @@ impl Clone for Context {
         Self {
             cache: self.cache.clone(),
             trace: self.trace.clone(),
+            span: self.span.clone(),
             gensym: Rc::clone(&self.gensym),
+            random: self.random.clone(),
+            precision: self.precision,
         }
     }
 }
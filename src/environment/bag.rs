@@ -1,6 +1,9 @@
 use std::{
     borrow::Borrow,
-    collections::{hash_map::RandomState, HashMap},
+    collections::{
+        hash_map::{self, RandomState},
+        HashMap,
+    },
     fmt, mem,
 };
 
@@ -104,6 +107,14 @@ impl Bag {
         self.0.get(key)
     }
 
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(match self.0 {
+            Repr::Empty => IterRepr::Single(None),
+            Repr::Single(ref key, ref var) => IterRepr::Single(Some((key, var))),
+            Repr::Map(ref map) => IterRepr::Map(map.iter()),
+        })
+    }
+
     pub fn merge(&mut self, other: Bag) {
         let mut bag = Repr::Empty;
         mem::swap(&mut self.0, &mut bag);
@@ -112,6 +123,25 @@ impl Bag {
     }
 }
 
+enum IterRepr<'a> {
+    Single(Option<(&'a Symbol, &'a Var)>),
+    Map(hash_map::Iter<'a, Symbol, Var>),
+}
+
+pub struct Iter<'a>(IterRepr<'a>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a Symbol, &'a Var);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.0 {
+            IterRepr::Single(ref mut entry) => entry.take(),
+            IterRepr::Map(ref mut it) => it.next(),
+        }
+    }
+}
+
 impl Default for Bag {
     #[inline]
     fn default() -> Self {
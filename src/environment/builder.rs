@@ -0,0 +1,216 @@
+use std::{fmt, num::NonZeroUsize};
+
+use im_rc::vector;
+
+use super::default;
+use crate::{proc::Parameters, Environment, Proc, Symbol, Value};
+
+/// A group of procedures of the standard library.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    Numbers,
+    Strings,
+    Procs,
+    Lists,
+    Output,
+    Reflection,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 6] = [
+        Self::Numbers,
+        Self::Strings,
+        Self::Procs,
+        Self::Lists,
+        Self::Output,
+        Self::Reflection,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Numbers => "numbers",
+            Self::Strings => "strings",
+            Self::Procs => "procs",
+            Self::Lists => "lists",
+            Self::Output => "output",
+            Self::Reflection => "reflection",
+        }
+    }
+
+    #[inline]
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+impl fmt::Display for Capability {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl From<Capability> for Value {
+    #[inline]
+    fn from(value: Capability) -> Self {
+        Value::Symbol(Symbol::Name(value.name().into()))
+    }
+}
+
+/// Builds a toplevel environment with a selection of the standard library.
+///
+/// Every capability starts denied, whether the builder comes from
+/// [`EnvironmentBuilder::new`], [`Default`] or [`Environment::builder`], and
+/// must be allowed explicitly. [`EnvironmentBuilder::all`] allows them all.
+///
+/// The procedures of a denied capability are still bound, but calling them
+/// raises a `capability-denied` error with the procedure name and the
+/// capability as arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnvironmentBuilder {
+    allowed: u8,
+}
+
+impl EnvironmentBuilder {
+    /// A builder with every capability denied.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { allowed: 0 }
+    }
+
+    /// A builder with every capability allowed.
+    pub fn all() -> Self {
+        Capability::ALL
+            .into_iter()
+            .fold(Self::new(), |me, capability| me.allow(capability))
+    }
+
+    #[inline]
+    pub const fn allow(mut self, capability: Capability) -> Self {
+        self.allowed |= capability.bit();
+        self
+    }
+
+    #[inline]
+    pub const fn deny(mut self, capability: Capability) -> Self {
+        self.allowed &= !capability.bit();
+        self
+    }
+
+    #[inline]
+    pub const fn allows(&self, capability: Capability) -> bool {
+        self.allowed & capability.bit() != 0
+    }
+
+    pub fn build(&self) -> Environment {
+        let me = Environment::new();
+
+        default::add_core(&me);
+        for capability in Capability::ALL {
+            if self.allows(capability) {
+                default::add(&me, capability);
+            } else {
+                deny(&me, capability);
+            }
+        }
+
+        me
+    }
+}
+
+/// Like [`EnvironmentBuilder::new`], every capability is denied.
+impl Default for EnvironmentBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Environment {
+    /// A builder with every capability denied, see [`EnvironmentBuilder`].
+    #[inline]
+    pub fn builder() -> EnvironmentBuilder {
+        EnvironmentBuilder::new()
+    }
+}
+
+/// Binds every procedure of `capability` to a stub raising
+/// `capability-denied`, macros stay macros so that their arguments are never
+/// evaluated.
+fn deny(me: &Environment, capability: Capability) {
+    let scratch = Environment::new();
+    default::add(&scratch, capability);
+
+    for (name, var) in scratch.bindings() {
+        let args = vector![name.clone().into(), capability.into()];
+        let mut stub = Proc::from_native(
+            Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(1) }),
+            None,
+            move |ctx, _values| Err(ctx.trace().error("capability-denied", Some(args.clone()))),
+        );
+        stub.set_name(name.clone());
+
+        let stub = if var.get().is_macro() {
+            Value::Macro(stub)
+        } else {
+            Value::Fn(stub)
+        };
+        me.define(name, stub);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use im_rc::vector;
+
+    use super::{Capability, EnvironmentBuilder};
+    use crate::{Environment, Interpreter, Value};
+
+    #[test]
+    fn sandbox() {
+        let interp = Interpreter::with_environment(
+            EnvironmentBuilder::all()
+                .deny(Capability::Output)
+                .deny(Capability::Reflection)
+                .build(),
+        );
+
+        assert_eq!(interp.eval_str("(+ 1 2)").unwrap(), 3.into());
+
+        let err = interp.call("println", vector!["hi".into()]).unwrap_err();
+        assert_eq!(err.name().as_str(), "capability-denied");
+        assert_eq!(
+            format!("{:?}", Value::from(err.args().unwrap())),
+            "(println output)"
+        );
+
+        let err = interp
+            .eval_str("(primitive-eval '(println \"hi\"))")
+            .unwrap_err();
+        assert_eq!(
+            format!("{}", err).lines().next(),
+            Some("<unknown>:1:1: capability-denied (primitive-eval reflection)")
+        );
+    }
+
+    #[test]
+    fn builder() {
+        let builder = Environment::builder().allow(Capability::Numbers);
+        assert!(builder.allows(Capability::Numbers));
+        assert!(!builder.allows(Capability::Lists));
+        assert!(!builder
+            .deny(Capability::Numbers)
+            .allows(Capability::Numbers));
+        assert_eq!(EnvironmentBuilder::default(), Environment::builder());
+        assert!(Capability::ALL
+            .into_iter()
+            .all(|c| !EnvironmentBuilder::default().allows(c)));
+
+        let interp = Interpreter::with_environment(builder.build());
+        assert_eq!(interp.eval_str("(not #f)").unwrap(), true.into());
+
+        let code = interp.parse("(defn f () 1)").unwrap();
+        let err = interp.eval(code).unwrap_err();
+        assert_eq!(err.name().as_str(), "capability-denied");
+    }
+}
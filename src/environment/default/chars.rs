@@ -0,0 +1,219 @@
+use std::{cmp::Ordering, num::NonZeroUsize};
+
+use im_rc::vector;
+use rug::Integer;
+use unicode_general_category::{get_general_category, GeneralCategory};
+
+use super::util::{define_fn, define_typed_fn};
+use crate::{proc::Parameters, Context, Environment, Str, Symbol, Value};
+
+/// The single character `c` maps to, or `c` itself if it maps to several
+/// characters, like `ß` that upcases to `SS`.
+#[inline]
+fn map_char<I: ExactSizeIterator<Item = char>>(c: char, f: fn(char) -> I) -> char {
+    let mut mapped = f(c);
+    if mapped.len() == 1 {
+        unsafe { mapped.next().unwrap_unchecked() }
+    } else {
+        c
+    }
+}
+
+#[inline]
+fn upcase(c: char) -> char {
+    map_char(c, char::to_uppercase)
+}
+
+#[inline]
+fn downcase(c: char) -> char {
+    map_char(c, char::to_lowercase)
+}
+
+pub fn add(me: &Environment) {
+    define_typed_fn(
+        me,
+        "char->integer",
+        Some("Return the Unicode code point of CHAR."),
+        |c: char| Ok(c as u32),
+    );
+
+    define_typed_fn(
+        me,
+        "integer->char",
+        Some("Return the character whose Unicode code point is N."),
+        |ctx: Context, n: Integer| {
+            n.to_u32()
+                .and_then(char::from_u32)
+                .ok_or_else(|| ctx.trace().error("out-of-range", Some(vector![n.into()])))
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "char-upcase",
+        Some("Return the upper case of CHAR, or CHAR if it has no single-character upper case."),
+        |c: char| Ok(upcase(c)),
+    );
+
+    define_typed_fn(
+        me,
+        "char-downcase",
+        Some("Return the lower case of CHAR, or CHAR if it has no single-character lower case."),
+        |c: char| Ok(downcase(c)),
+    );
+
+    define_typed_fn(
+        me,
+        "char-alphabetic?",
+        Some("Return `#t' if CHAR is alphabetic according to Unicode."),
+        |c: char| Ok(c.is_alphabetic()),
+    );
+
+    define_typed_fn(
+        me,
+        "char-numeric?",
+        Some("Return `#t' if CHAR is a decimal digit in any script, like `7' or `٧'."),
+        |c: char| Ok(get_general_category(c) == GeneralCategory::DecimalNumber),
+    );
+
+    define_typed_fn(
+        me,
+        "char-whitespace?",
+        Some("Return `#t' if CHAR is whitespace according to Unicode."),
+        |c: char| Ok(c.is_whitespace()),
+    );
+
+    define_typed_fn(
+        me,
+        "char-upper-case?",
+        Some("Return `#t' if CHAR is an upper case letter."),
+        |c: char| Ok(c.is_uppercase()),
+    );
+
+    define_typed_fn(
+        me,
+        "char-lower-case?",
+        Some("Return `#t' if CHAR is a lower case letter."),
+        |c: char| Ok(c.is_lowercase()),
+    );
+
+    define_typed_fn(
+        me,
+        "char-general-category",
+        Some("Return the Unicode general category of CHAR as a symbol, like `Lu' or `Nd'."),
+        |c: char| {
+            Ok(Symbol::Name(Str::from(
+                get_general_category(c).abbreviation(),
+            )))
+        },
+    );
+
+    fn define_cmp(env: &Environment, name: &'static str, fold: bool, f: fn(Ordering) -> bool) {
+        define_fn(
+            env,
+            name,
+            Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(1) }),
+            Option::<&str>::None,
+            move |ctx, values| {
+                let mut chars = Vec::with_capacity(values.len());
+                for v in values {
+                    match v {
+                        Value::Character(c) if fold => chars.push(downcase(upcase(c))),
+                        Value::Character(c) => chars.push(c),
+                        v => return Err(ctx.trace().error("wrong-type-arg", Some(vector![v]))),
+                    }
+                }
+
+                Ok(chars.windows(2).all(|w| f(w[0].cmp(&w[1]))).into())
+            },
+        );
+    }
+
+    for (name, f) in [
+        ("char=?", Ordering::is_eq as fn(Ordering) -> bool),
+        ("char<?", Ordering::is_lt),
+        ("char>?", Ordering::is_gt),
+        ("char<=?", Ordering::is_le),
+        ("char>=?", Ordering::is_ge),
+    ] {
+        define_cmp(me, name, false, f);
+    }
+
+    for (name, f) in [
+        ("char-ci=?", Ordering::is_eq as fn(Ordering) -> bool),
+        ("char-ci<?", Ordering::is_lt),
+        ("char-ci>?", Ordering::is_gt),
+        ("char-ci<=?", Ordering::is_le),
+        ("char-ci>=?", Ordering::is_ge),
+    ] {
+        define_cmp(me, name, true, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::environment::default::test_util::{eval, eval_err};
+
+    #[test]
+    fn conversions() {
+        assert_eq!(eval(r"(char->integer #\a)"), 97.into());
+        assert_eq!(eval(r"(char->integer #\x1F600)"), 0x1f600.into());
+        assert_eq!(eval("(integer->char 955)"), 'λ'.into());
+        assert_eq!(
+            eval_err("(integer->char #xD800)").name().as_str(),
+            "out-of-range"
+        );
+        assert_eq!(
+            eval_err("(integer->char -1)").name().as_str(),
+            "out-of-range"
+        );
+        assert_eq!(
+            eval_err(r#"(char->integer "a")"#).name().as_str(),
+            "wrong-type-arg"
+        );
+
+        assert_eq!(eval(r"(char-upcase #\ä)"), 'Ä'.into());
+        assert_eq!(eval(r"(char-upcase #\ß)"), 'ß'.into());
+        assert_eq!(eval(r"(char-downcase #\Σ)"), 'σ'.into());
+        assert_eq!(eval(r"(char-downcase #\1)"), '1'.into());
+    }
+
+    #[test]
+    fn classification() {
+        assert_eq!(eval(r"(char-alphabetic? #\λ)"), true.into());
+        assert_eq!(eval(r"(char-alphabetic? #\1)"), false.into());
+        assert_eq!(eval(r"(char-numeric? #\٧)"), true.into());
+        assert_eq!(eval(r"(char-numeric? #\½)"), false.into());
+        assert_eq!(eval(r"(char-whitespace? #\tab)"), true.into());
+        assert_eq!(eval(r"(char-whitespace? #\x3000)"), true.into());
+        assert_eq!(eval(r"(char-upper-case? #\A)"), true.into());
+        assert_eq!(eval(r"(char-lower-case? #\A)"), false.into());
+
+        assert_eq!(format!("{}", eval(r"(char-general-category #\A)")), "Lu");
+        assert_eq!(format!("{}", eval(r"(char-general-category #\7)")), "Nd");
+        assert_eq!(
+            format!("{}", eval(r"(char-general-category #\space)")),
+            "Zs"
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(eval(r"(char<? #\a #\b #\c)"), true.into());
+        assert_eq!(eval(r"(char<? #\a #\a)"), false.into());
+        assert_eq!(eval(r"(char<=? #\a #\a #\b)"), true.into());
+        assert_eq!(eval(r"(char>? #\b #\a)"), true.into());
+        assert_eq!(eval(r"(char>=? #\a #\b)"), false.into());
+        assert_eq!(eval(r"(char=? #\a #\a #\a)"), true.into());
+        assert_eq!(eval(r"(char=? #\a #\A)"), false.into());
+        assert_eq!(eval(r"(char-ci=? #\a #\A)"), true.into());
+        assert_eq!(eval(r"(char-ci<? #\a #\B)"), true.into());
+        assert_eq!(eval(r"(char-ci>? #\a #\B)"), false.into());
+        assert_eq!(eval(r"(char-ci<=? #\σ #\Σ)"), true.into());
+        assert_eq!(eval(r"(char-ci>=? #\Z #\a)"), true.into());
+        assert_eq!(
+            eval_err(r"(char<? #\a 1)").name().as_str(),
+            "wrong-type-arg"
+        );
+    }
+}
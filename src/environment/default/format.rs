@@ -0,0 +1,312 @@
+use std::{num::NonZeroUsize, rc::Rc};
+
+use im_rc::{vector, Vector};
+use rug::Integer;
+
+use super::util::define_fn;
+use crate::{
+    parser::reader::util::CountChars, proc::Parameters, Context, Environment, Error, FromValue,
+    OutputPort, Str, Value,
+};
+
+/// The largest minimum width or repeat count a directive can have.
+const MAX_COLUMNS: usize = 1 << 16;
+
+/// A `~` directive of a `format` template, like `~5,'0x`.
+struct Directive {
+    /// The minimum number of columns the output takes, or how many times
+    /// `~%` and `~~` are repeated.
+    mincol: Option<usize>,
+    pad: char,
+    /// Whether `@` was given: text is aligned to the right and numbers
+    /// always get a sign.
+    at: bool,
+    kind: char,
+}
+
+/// Reads the directive after a `~` from `template`, or `None` if it is
+/// malformed or its width is over [`MAX_COLUMNS`].
+fn directive(template: &mut std::str::Chars) -> Option<Directive> {
+    let mut mincol = None;
+    let mut pad = ' ';
+    let mut at = false;
+
+    let mut c = template.next()?;
+    while let Some(d) = c.to_digit(10) {
+        let n = mincol.unwrap_or(0usize) * 10 + d as usize;
+        if n > MAX_COLUMNS {
+            return None;
+        }
+        mincol = Some(n);
+        c = template.next()?;
+    }
+    if c == ',' {
+        if template.next()? != '\'' {
+            return None;
+        }
+        pad = template.next()?;
+        c = template.next()?;
+    }
+    if c == '@' {
+        at = true;
+        c = template.next()?;
+    }
+
+    Some(Directive {
+        mincol,
+        pad,
+        at,
+        kind: c.to_ascii_lowercase(),
+    })
+}
+
+/// Appends `s` to `out` padded with `pad` to at least `mincol` characters,
+/// on the left if `left`.
+fn pad(out: &mut String, s: &str, mincol: Option<usize>, pad: char, left: bool) {
+    let fill = mincol.unwrap_or(0).saturating_sub(s.count_chars());
+    if !left {
+        out.push_str(s);
+    }
+    for _ in 0..fill {
+        out.push(pad);
+    }
+    if left {
+        out.push_str(s);
+    }
+}
+
+/// Writes `template` with its directives replaced by `args`.
+fn format(ctx: &Context, template: &Str, mut args: Vector<Value>) -> Result<String, Error> {
+    let error = |name: &'static str, args: Option<Vector<Value>>| ctx.trace().error(name, args);
+    let malformed = || error("format-error", Some(vector![template.clone().into()]));
+
+    let mut out = String::with_capacity(template.bytes_len());
+    let mut chars = template.as_str().chars();
+
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+
+        let d = directive(&mut chars).ok_or_else(malformed)?;
+        match d.kind {
+            '%' | '~' => {
+                let c = if d.kind == '%' { '\n' } else { '~' };
+                for _ in 0..d.mincol.unwrap_or(1) {
+                    out.push(c);
+                }
+            }
+            'a' | 's' | 'd' | 'x' | 'b' => {
+                let arg = args
+                    .pop_front()
+                    .ok_or_else(|| error("wrong-number-of-args", None))?;
+
+                let s = match d.kind {
+                    'a' => arg.to_string(),
+                    's' => format!("{:?}", arg),
+                    _ => {
+                        let n = Integer::from_value(arg)
+                            .map_err(|v| error("wrong-type-arg", Some(vector![v])))?;
+                        let radix = match d.kind {
+                            'd' => 10,
+                            'x' => 16,
+                            _ => 2,
+                        };
+                        let sign = if d.at && n >= 0 { "+" } else { "" };
+                        format!("{}{}", sign, n.to_string_radix(radix))
+                    }
+                };
+
+                let left = matches!(d.kind, 'd' | 'x' | 'b') || d.at;
+                pad(&mut out, &s, d.mincol, d.pad, left);
+            }
+            _ => return Err(malformed()),
+        }
+    }
+
+    if !args.is_empty() {
+        return Err(error("wrong-number-of-args", None));
+    }
+    Ok(out)
+}
+
+pub fn add(me: &Environment) {
+    define_fn(
+        me,
+        "format",
+        Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(3) }),
+        Some("Write ARGS formatted by TEMPLATE to port DEST, or return the text if DEST is `#f'."),
+        |mut ctx, mut values| {
+            let (dest, template) = match (values.pop_front(), values.pop_front()) {
+                (Some(dest), Some(Value::String(template))) => (dest, template),
+                (Some(_), Some(v)) => {
+                    return Err(ctx.trace().error("wrong-type-arg", Some(vector![v])))
+                }
+                _ => return Err(ctx.trace().error("wrong-number-of-args", None)),
+            };
+
+            match dest {
+                Value::Boolean(false) => {
+                    let s = format(&ctx, &template, values)?;
+                    Ok(ctx.make_string(s).into())
+                }
+                dest => match Rc::<OutputPort>::from_value(dest) {
+                    Ok(port) => {
+                        let s = format(&ctx, &template, values)?;
+                        port.write_str(&s).map_err(|err| {
+                            ctx.trace()
+                                .error("io-error", Some(vector![err.to_string().into()]))
+                        })?;
+                        Ok(Value::Nil)
+                    }
+                    Err(v) => Err(ctx.trace().error("wrong-type-arg", Some(vector![v]))),
+                },
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io, rc::Rc};
+
+    use crate::environment::default::test_util::{eval, eval_err};
+    use crate::{Interpreter, OutputPort, Value};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn directives() {
+        assert_eq!(
+            eval(r#"(format #f "~a and ~s" "ciao" "ciao")"#),
+            r#"ciao and "ciao""#.into()
+        );
+        assert_eq!(eval(r#"(format #f "~a" '(1 #\a))"#), "(1 a)".into());
+        assert_eq!(
+            eval(r#"(format #f "~d ~x ~b" 42 255 5)"#),
+            "42 ff 101".into()
+        );
+        assert_eq!(
+            eval(r#"(format #f "~x" (- (expt 2 70)))"#),
+            "-400000000000000000".into()
+        );
+        assert_eq!(
+            eval(r#"(format #f "a~%b~2%c ~~ ~3~")"#),
+            "a\nb\n\nc ~ ~~~".into()
+        );
+        assert_eq!(eval(r#"(format #f "plain")"#), "plain".into());
+    }
+
+    #[test]
+    fn padding() {
+        assert_eq!(eval(r#"(format #f "[~6a]" "ab")"#), "[ab    ]".into());
+        assert_eq!(eval(r#"(format #f "[~6@a]" "ab")"#), "[    ab]".into());
+        assert_eq!(eval(r#"(format #f "[~6,'.s]" "λ")"#), r#"["λ"...]"#.into());
+        assert_eq!(eval(r#"(format #f "[~5d]" 42)"#), "[   42]".into());
+        assert_eq!(eval(r#"(format #f "~8,'0b" 5)"#), "00000101".into());
+        assert_eq!(eval(r#"(format #f "~4,'0X" 255)"#), "00ff".into());
+        assert_eq!(eval(r#"(format #f "~@d ~@d" 3 -3)"#), "+3 -3".into());
+        assert_eq!(eval(r#"(format #f "~2a" "long")"#), "long".into());
+    }
+
+    #[test]
+    fn ports() {
+        let interp = Interpreter::new();
+        let buffer = Buffer::default();
+        interp.define_value("out", OutputPort::new(buffer.clone()));
+
+        assert_eq!(
+            interp.eval_str(r#"(format out "~a=~d~%" "x" 1)"#).unwrap(),
+            Value::Nil
+        );
+        interp.eval_str(r#"(format out "done")"#).unwrap();
+        assert_eq!(&*buffer.0.borrow(), b"x=1\ndone");
+
+        assert_eq!(interp.eval_str("(output-port? out)").unwrap(), true.into());
+        assert_eq!(
+            interp
+                .eval_str("(output-port? (current-output-port))")
+                .unwrap(),
+            true.into()
+        );
+        assert_eq!(interp.eval_str("(output-port? 1)").unwrap(), false.into());
+        assert_eq!(
+            interp
+                .eval_str("(eq? (current-output-port) (current-output-port))")
+                .unwrap(),
+            true.into()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = eval_err(r#"(format #f "~q" 1)"#);
+        assert_eq!(err.name().as_str(), "format-error");
+        assert_eq!(err.args(), Some(im_rc::vector!["~q".into()]));
+        assert_eq!(
+            eval_err(r#"(format #f "~5,0d" 1)"#).name().as_str(),
+            "format-error"
+        );
+        assert_eq!(
+            eval_err(r#"(format #f "oops~")"#).name().as_str(),
+            "format-error"
+        );
+
+        let err = eval_err(r#"(format #f "~99999999999999999a" 1)"#);
+        assert_eq!(err.name().as_str(), "format-error");
+        assert_eq!(
+            err.args(),
+            Some(im_rc::vector!["~99999999999999999a".into()])
+        );
+        assert_eq!(
+            eval_err(r#"(format #f "~18446744073709551615%")"#)
+                .name()
+                .as_str(),
+            "format-error"
+        );
+        assert_eq!(
+            eval(r#"(format #f "~65536a" "")"#),
+            " ".repeat(65536).into()
+        );
+        assert_eq!(
+            eval_err(r#"(format #f "~a ~a" 1)"#).name().as_str(),
+            "wrong-number-of-args"
+        );
+        assert_eq!(
+            eval_err(r#"(format #f "~a" 1 2)"#).name().as_str(),
+            "wrong-number-of-args"
+        );
+        assert_eq!(
+            eval_err("(format #f)").name().as_str(),
+            "wrong-number-of-args"
+        );
+        assert_eq!(
+            eval_err(r#"(format #f 'x)"#).name().as_str(),
+            "wrong-type-arg"
+        );
+        assert_eq!(
+            eval_err(r#"(format #f "~d" "1")"#).name().as_str(),
+            "wrong-type-arg"
+        );
+        assert_eq!(
+            eval_err(r#"(format #f "~d" 1.5)"#).name().as_str(),
+            "wrong-type-arg"
+        );
+        assert_eq!(
+            eval_err(r#"(format #t "x")"#).name().as_str(),
+            "wrong-type-arg"
+        );
+    }
+}
@@ -1,13 +1,30 @@
-use std::{
-    num::NonZeroUsize,
-    ops::{AddAssign, DivAssign, MulAssign, Neg, SubAssign},
-};
+use std::{cmp::Ordering, num::NonZeroUsize, rc::Rc};
 
 use im_rc::vector;
-use rug::Integer;
+use rug::{Float, Integer, Rational};
+
+use super::util::{define_fn, define_macro, define_typed_fn};
+use crate::{
+    number::{self, Number},
+    parser::reader,
+    proc::Parameters,
+    Context, Environment, Error, Foreign, FromValue, RandomState, Str, Symbol, Value,
+};
+
+/// The largest number of bits of an exact result of `expt` or `ash`, about 5
+/// million decimal digits. Larger results fail with `out-of-range` instead
+/// of exhausting memory.
+const MAX_BITS: u64 = 1 << 24;
 
-use super::util::{define_fn, define_macro};
-use crate::{proc::Parameters, Environment, Str, Symbol, Value};
+#[inline]
+fn number(ctx: &Context, value: Value) -> Result<Number, Error> {
+    Number::from_value(value).map_err(|v| ctx.trace().error("wrong-type-arg", Some(vector![v])))
+}
+
+#[inline]
+fn integer(ctx: &Context, value: Value) -> Result<Integer, Error> {
+    Integer::from_value(value).map_err(|v| ctx.trace().error("wrong-type-arg", Some(vector![v])))
+}
 
 pub fn add(me: &Environment) {
     define_fn(
@@ -21,6 +38,28 @@ pub fn add(me: &Environment) {
         },
     );
 
+    define_fn(
+        me,
+        "rational?",
+        Parameters::Exact(1),
+        Option::<&str>::None,
+        |_ctx, mut values| {
+            let x = values.remove(0);
+            Ok((x.is_integer() || x.is_rational()).into())
+        },
+    );
+
+    define_fn(
+        me,
+        "float?",
+        Parameters::Exact(1),
+        Option::<&str>::None,
+        |_ctx, mut values| {
+            let x = values.remove(0);
+            Ok(x.is_float().into())
+        },
+    );
+
     define_fn(
         me,
         "+",
@@ -28,20 +67,12 @@ pub fn add(me: &Environment) {
         Some("Return the sum of all parameter values. Return 0 if called without any parameters."),
         |ctx, mut values| match values.len() {
             0 => Ok(Integer::from(0).into()),
-            1 => Ok(values.remove(0)),
+            1 => Ok(number(&ctx, values.remove(0))?.into()),
             _ => {
-                let mut acc = match values.remove(0) {
-                    Value::Integer(i) => i,
-                    _ => return Err(ctx.trace().error("wrong-type-arg", None)),
-                };
-
+                let mut acc = number(&ctx, values.remove(0))?;
                 for v in values {
-                    match v {
-                        Value::Integer(i) => acc.add_assign(i),
-                        _ => return Err(ctx.trace().error("wrong-type-arg", None)),
-                    }
+                    acc = acc.add(number(&ctx, v)?, ctx.precision());
                 }
-
                 Ok(acc.into())
             }
         },
@@ -50,32 +81,22 @@ pub fn add(me: &Environment) {
     define_fn(
         me,
         "-",
-        Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(1) }),
+        Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(2) }),
         Some(
             "If called with one argument Z1, -Z1 returned. Otherwise the sum of all but the first \
                 argument are subtracted from the first argument.",
         ),
-        |ctx, mut values| match values.len() {
-            0 => unreachable!(),
-            1 => match values.remove(0) {
-                Value::Integer(i) => Ok(i.neg().into()),
-                _ => Err(ctx.trace().error("wrong-type-arg", None)),
-            },
-            _ => {
-                let mut acc = match values.remove(0) {
-                    Value::Integer(i) => i,
-                    _ => return Err(ctx.trace().error("wrong-type-arg", None)),
-                };
-
-                for v in values.into_iter() {
-                    match v {
-                        Value::Integer(i) => acc.sub_assign(i),
-                        _ => return Err(ctx.trace().error("wrong-type-arg", None)),
-                    }
-                }
+        |ctx, mut values| {
+            let first = number(&ctx, values.remove(0))?;
+            if values.is_empty() {
+                return Ok(first.neg().into());
+            }
 
-                Ok(acc.into())
+            let mut acc = first;
+            for v in values {
+                acc = acc.sub(number(&ctx, v)?, ctx.precision());
             }
+            Ok(acc.into())
         },
     );
 
@@ -86,20 +107,12 @@ pub fn add(me: &Environment) {
         Some("Return the product of all arguments.  If called without arguments, 1 is returned."),
         |ctx, mut values| match values.len() {
             0 => Ok(Integer::from(1).into()),
-            1 => Ok(values.remove(0)),
+            1 => Ok(number(&ctx, values.remove(0))?.into()),
             _ => {
-                let mut acc = match values.remove(0) {
-                    Value::Integer(i) => i,
-                    _ => return Err(ctx.trace().error("wrong-type-arg", None)),
-                };
-
+                let mut acc = number(&ctx, values.remove(0))?;
                 for v in values {
-                    match v {
-                        Value::Integer(i) => acc.mul_assign(i),
-                        _ => return Err(ctx.trace().error("wrong-type-arg", None)),
-                    }
+                    acc = acc.mul(number(&ctx, v)?, ctx.precision());
                 }
-
                 Ok(acc.into())
             }
         },
@@ -109,30 +122,28 @@ pub fn add(me: &Environment) {
         me,
         "/",
         Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(3) }),
-        Some("Divide the first argument by the product of the remaining arguments."),
+        Some(
+            "Divide the first argument by the product of the remaining arguments. The result is \
+                an exact rational if all arguments are exact.",
+        ),
         |ctx, mut values| {
-            let mut acc = if let Value::Integer(i) = values.remove(0) {
-                i
-            } else {
-                return Err(ctx.trace().error("wrong-type-arg", None));
-            };
-
+            let mut acc = number(&ctx, values.remove(0))?;
             for v in values {
-                let i = if let Value::Integer(i) = v {
-                    i
-                } else {
-                    return Err(ctx.trace().error("wrong-type-arg", None));
-                };
-                acc.div_assign(i);
+                let v = number(&ctx, v)?;
+                if acc.is_exact() && v.is_exact() && v.is_zero() {
+                    return Err(ctx
+                        .trace()
+                        .error("divide-by-zero", Some(vector![acc.into(), v.into()])));
+                }
+                acc = acc.div(v, ctx.precision());
             }
-
-            Ok(Value::Integer(acc))
+            Ok(acc.into())
         },
     );
 
     fn define_cmp<S1, S2, F>(env: &Environment, name: S1, doc: Option<S2>, f: F)
     where
-        F: (Fn(&Integer, &Integer) -> bool) + 'static,
+        F: (Fn(Option<Ordering>) -> bool) + 'static,
         S1: Into<Str>,
         S2: Into<Str>,
     {
@@ -143,19 +154,13 @@ pub fn add(me: &Environment) {
             doc,
             move |ctx, mut values| {
                 if let Some(prev) = values.pop_front() {
-                    if let Value::Integer(mut prev) = prev {
-                        while let Some(next) = values.pop_front() {
-                            if let Value::Integer(next) = next {
-                                if !f(&prev, &next) {
-                                    return Ok(false.into());
-                                }
-                                prev = next;
-                            } else {
-                                return Err(ctx.trace().error("wrong-type-arg", None));
-                            }
+                    let mut prev = number(&ctx, prev)?;
+                    while let Some(next) = values.pop_front() {
+                        let next = number(&ctx, next)?;
+                        if !f(prev.partial_cmp(&next)) {
+                            return Ok(false.into());
                         }
-                    } else {
-                        return Err(ctx.trace().error("wrong-type-arg", None));
+                        prev = next;
                     }
                 }
 
@@ -168,28 +173,28 @@ pub fn add(me: &Environment) {
         me,
         "<",
         Some("Return `#t' if the list of parameters is monotonically increasing."),
-        Integer::lt,
+        |o| o.is_some_and(Ordering::is_lt),
     );
 
     define_cmp(
         me,
         "<=",
         Some("Return `#t' if the list of parameters is monotonically non-decreasing."),
-        Integer::le,
+        |o| o.is_some_and(Ordering::is_le),
     );
 
     define_cmp(
         me,
         ">",
         Some("Return `#t' if the list of parameters is monotonically decreasing."),
-        Integer::gt,
+        |o| o.is_some_and(Ordering::is_gt),
     );
 
     define_cmp(
         me,
         ">=",
         Some("Return `#t' if the list of parameters is monotonically non-increasing."),
-        Integer::ge,
+        |o| o.is_some_and(Ordering::is_ge),
     );
 
     define_fn(
@@ -198,15 +203,626 @@ pub fn add(me: &Environment) {
         Parameters::Exact(1),
         Option::<&str>::None,
         |ctx, mut args| {
-            if let Value::Integer(mut n) = args.remove(0) {
-                n.add_assign(1);
-                Ok(n.into())
+            let n = number(&ctx, args.remove(0))?;
+            Ok(n.add(Number::Fixnum(1), ctx.precision()).into())
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "numerator",
+        Some("Return the numerator of the rational Q, in lowest terms."),
+        |q: Rational| Ok(q.into_numer_denom().0),
+    );
+
+    define_typed_fn(
+        me,
+        "denominator",
+        Some("Return the denominator of the rational Q, in lowest terms."),
+        |q: Rational| Ok(q.into_numer_denom().1),
+    );
+
+    for name in ["exact->inexact", "inexact"] {
+        define_fn(
+            me,
+            name,
+            Parameters::Exact(1),
+            Some(
+                "Convert the number Z to the nearest floating point number, big floats are left \
+                    unchanged.",
+            ),
+            |ctx, mut args| Ok(number(&ctx, args.remove(0))?.into_inexact(false, 0).into()),
+        );
+    }
+
+    for name in ["inexact->exact", "exact"] {
+        define_fn(
+            me,
+            name,
+            Parameters::Exact(1),
+            Some(
+                "Convert the number Z to an exact number with the same value. Infinities and NaN \
+                    have no exact representation.",
+            ),
+            |ctx, mut args| {
+                let z = number(&ctx, args.remove(0))?;
+                z.to_exact()
+                    .map(Into::into)
+                    .ok_or_else(|| ctx.trace().error("out-of-range", Some(vector![z.into()])))
+            },
+        );
+    }
+
+    define_typed_fn(
+        me,
+        "exact?",
+        Some("Return `#t' if Z is an exact number, that is an integer or a rational."),
+        |z: Number| Ok(z.is_exact()),
+    );
+
+    define_typed_fn(
+        me,
+        "inexact?",
+        Some("Return `#t' if Z is a float or a big float."),
+        |z: Number| Ok(!z.is_exact()),
+    );
+
+    define_typed_fn(
+        me,
+        "floor",
+        Some("Return the largest integral value not greater than X."),
+        |x: Number| Ok(x.floor()),
+    );
+
+    define_typed_fn(
+        me,
+        "ceiling",
+        Some("Return the smallest integral value not less than X."),
+        |x: Number| Ok(x.ceil()),
+    );
+
+    define_typed_fn(
+        me,
+        "truncate",
+        Some("Return the integral value closest to X whose absolute value is not greater."),
+        |x: Number| Ok(x.trunc()),
+    );
+
+    define_typed_fn(
+        me,
+        "round",
+        Some("Return the integral value closest to X, rounding to even when X is halfway."),
+        |x: Number| Ok(x.round()),
+    );
+
+    fn define_division<S1, S2, T>(
+        env: &Environment,
+        name: S1,
+        doc: Option<S2>,
+        f: fn(Integer, Integer) -> T,
+    ) where
+        S1: Into<Str>,
+        S2: Into<Str>,
+        T: Into<Value> + 'static,
+    {
+        define_typed_fn(
+            env,
+            name,
+            doc,
+            move |ctx: Context, n: Integer, d: Integer| {
+                if d.cmp0() == Ordering::Equal {
+                    Err(ctx
+                        .trace()
+                        .error("divide-by-zero", Some(vector![n.into(), d.into()])))
+                } else {
+                    Ok(f(n, d))
+                }
+            },
+        );
+    }
+
+    define_division(
+        me,
+        "quotient",
+        Some("Return the quotient of N and D, rounded towards zero."),
+        |n, d| n.div_rem(d).0,
+    );
+
+    define_division(
+        me,
+        "remainder",
+        Some("Return the remainder of N divided by D, with the sign of N."),
+        |n, d| n.div_rem(d).1,
+    );
+
+    define_division(
+        me,
+        "modulo",
+        Some("Return N modulo D, with the sign of D."),
+        |n, d| n.div_rem_floor(d).1,
+    );
+
+    define_division(
+        me,
+        "floor/",
+        Some(
+            "Return a list of the quotient of N and D rounded towards negative infinity and the \
+                matching remainder, with the sign of D.",
+        ),
+        |n, d| {
+            let (q, r) = n.div_rem_floor(d);
+            Value::from(vector![q.into(), r.into()])
+        },
+    );
+
+    define_division(
+        me,
+        "truncate/",
+        Some(
+            "Return a list of the quotient of N and D rounded towards zero and the matching \
+                remainder, with the sign of N.",
+        ),
+        |n, d| {
+            let (q, r) = n.div_rem(d);
+            Value::from(vector![q.into(), r.into()])
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "abs",
+        Some("Return the absolute value of X."),
+        |x: Number| Ok(x.abs()),
+    );
+
+    fn define_extremum<S1, S2>(env: &Environment, name: S1, doc: Option<S2>, pick: Ordering)
+    where
+        S1: Into<Str>,
+        S2: Into<Str>,
+    {
+        define_fn(
+            env,
+            name,
+            Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(2) }),
+            doc,
+            move |ctx, values| {
+                let (mut inexact, mut big) = (false, false);
+                let mut best: Option<Number> = None;
+
+                for v in values {
+                    let v = number(&ctx, v)?;
+                    inexact |= !v.is_exact();
+                    big |= v.is_big();
+                    best = Some(match best {
+                        Some(b) if !v.is_nan() && v.partial_cmp(&b) != Some(pick) => b,
+                        _ => v,
+                    });
+                }
+
+                let best = unsafe { best.unwrap_unchecked() };
+                Ok(if inexact {
+                    best.into_inexact(big, ctx.precision()).into()
+                } else {
+                    best.into()
+                })
+            },
+        );
+    }
+
+    define_extremum(
+        me,
+        "max",
+        Some(
+            "Return the maximum of all parameter values. The result is inexact if any argument is.",
+        ),
+        Ordering::Greater,
+    );
+
+    define_extremum(
+        me,
+        "min",
+        Some(
+            "Return the minimum of all parameter values. The result is inexact if any argument is.",
+        ),
+        Ordering::Less,
+    );
+
+    define_fn(
+        me,
+        "gcd",
+        Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(1) }),
+        Some(
+            "Return the greatest common divisor of all parameter values. Return 0 if called \
+                without any parameters.",
+        ),
+        |ctx, values| {
+            let mut acc = Integer::new();
+            for v in values {
+                acc = acc.gcd(&integer(&ctx, v)?);
+            }
+            Ok(acc.into())
+        },
+    );
+
+    define_fn(
+        me,
+        "lcm",
+        Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(1) }),
+        Some(
+            "Return the least common multiple of all parameter values. Return 1 if called \
+                without any parameters.",
+        ),
+        |ctx, values| {
+            let mut acc = Integer::from(1);
+            for v in values {
+                acc = acc.lcm(&integer(&ctx, v)?);
+            }
+            Ok(acc.into())
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "expt",
+        Some(
+            "Return Z1 raised to the power Z2. The result is exact if Z1 is exact and Z2 is an \
+                integer.",
+        ),
+        |ctx: Context, z1: Number, z2: Number| match z2.to_i32() {
+            Some(e) if z1.is_exact() => match e {
+                Some(e) if e < 0 && z1.is_zero() => Err(ctx
+                    .trace()
+                    .error("divide-by-zero", Some(vector![z1.into(), z2.into()]))),
+                Some(e) if z1.pow_bits(e) <= MAX_BITS => Ok(z1.pow(e, ctx.precision())),
+                _ => Err(ctx
+                    .trace()
+                    .error("out-of-range", Some(vector![z1.into(), z2.into()]))),
+            },
+            Some(Some(e)) => Ok(z1.pow(e, ctx.precision())),
+            _ => Ok(z1.powf(z2, ctx.precision())),
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "exact-integer-sqrt",
+        Some(
+            "Return a list of the largest integer S with S*S <= K and the remainder K - S*S, \
+                K must not be negative.",
+        ),
+        |ctx: Context, k: Integer| {
+            if k.cmp0() == Ordering::Less {
+                return Err(ctx.trace().error("out-of-range", Some(vector![k.into()])));
+            }
+            let (s, r) = k.sqrt_rem(Integer::new());
+            Ok(Value::from(vector![s.into(), r.into()]))
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "even?",
+        Some("Return `#t' if the integer N is even."),
+        |n: Integer| Ok(n.is_even()),
+    );
+
+    define_typed_fn(
+        me,
+        "odd?",
+        Some("Return `#t' if the integer N is odd."),
+        |n: Integer| Ok(n.is_odd()),
+    );
+
+    define_typed_fn(
+        me,
+        "zero?",
+        Some("Return `#t' if X is zero."),
+        |x: Number| Ok(x.is_zero()),
+    );
+
+    define_typed_fn(
+        me,
+        "positive?",
+        Some("Return `#t' if X is greater than zero."),
+        |x: Number| Ok(x.sign() == Some(Ordering::Greater)),
+    );
+
+    define_typed_fn(
+        me,
+        "negative?",
+        Some("Return `#t' if X is less than zero."),
+        |x: Number| Ok(x.sign() == Some(Ordering::Less)),
+    );
+
+    fn define_bitwise<S1, S2>(
+        env: &Environment,
+        name: S1,
+        doc: Option<S2>,
+        identity: i32,
+        f: fn(Integer, Integer) -> Integer,
+    ) where
+        S1: Into<Str>,
+        S2: Into<Str>,
+    {
+        define_fn(
+            env,
+            name,
+            Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(1) }),
+            doc,
+            move |ctx, values| {
+                let mut acc = Integer::from(identity);
+                for v in values {
+                    acc = f(acc, integer(&ctx, v)?);
+                }
+                Ok(acc.into())
+            },
+        );
+    }
+
+    define_bitwise(
+        me,
+        "logand",
+        Some("Return the bitwise AND of the integer arguments, -1 if called without any."),
+        -1,
+        |a, b| a & b,
+    );
+
+    define_bitwise(
+        me,
+        "logior",
+        Some("Return the bitwise OR of the integer arguments, 0 if called without any."),
+        0,
+        |a, b| a | b,
+    );
+
+    define_bitwise(
+        me,
+        "logxor",
+        Some("Return the bitwise XOR of the integer arguments, 0 if called without any."),
+        0,
+        |a, b| a ^ b,
+    );
+
+    define_typed_fn(
+        me,
+        "lognot",
+        Some("Return the bitwise complement of N, that is -N - 1."),
+        |n: Integer| Ok(!n),
+    );
+
+    define_typed_fn(
+        me,
+        "ash",
+        Some(
+            "Return N shifted left by COUNT bits, or right if COUNT is negative. Right shifts \
+                round towards negative infinity.",
+        ),
+        |ctx: Context, n: Integer, count: Integer| {
+            if count.cmp0() != Ordering::Less {
+                match count.to_u32() {
+                    Some(_) if n.cmp0() == Ordering::Equal => Ok(n),
+                    Some(c) if u64::from(n.significant_bits()) + u64::from(c) <= MAX_BITS => {
+                        Ok(n << c)
+                    }
+                    _ => Err(ctx
+                        .trace()
+                        .error("out-of-range", Some(vector![n.into(), count.into()]))),
+                }
             } else {
-                Err(ctx.trace().error("wrong-type-arg", None))
+                match (-count).to_u32() {
+                    Some(count) => Ok(n >> count),
+                    None if n.cmp0() == Ordering::Less => Ok(Integer::from(-1)),
+                    None => Ok(Integer::new()),
+                }
+            }
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "bit-count",
+        Some(
+            "Return the number of 1 bits in N, or of 0 bits if N is negative, in two's \
+                complement.",
+        ),
+        |n: Integer| {
+            let count = match n.count_ones() {
+                Some(count) => count,
+                None => unsafe { (!n).count_ones().unwrap_unchecked() },
+            };
+            Ok(Integer::from(count))
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "bit-set?",
+        Some("Return `#t' if bit INDEX of N is 1 in two's complement."),
+        |ctx: Context, index: Integer, n: Integer| {
+            if index.cmp0() == Ordering::Less {
+                return Err(ctx
+                    .trace()
+                    .error("out-of-range", Some(vector![index.into()])));
+            }
+            Ok(match index.to_u32() {
+                Some(index) => n.get_bit(index),
+                None => n.cmp0() == Ordering::Less,
+            })
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "integer-length",
+        Some(
+            "Return the number of bits needed to represent N in two's complement, without the \
+                sign bit.",
+        ),
+        |n: Integer| {
+            let bits = if n.cmp0() == Ordering::Less {
+                (!n).significant_bits()
+            } else {
+                n.significant_bits()
+            };
+            Ok(Integer::from(bits))
+        },
+    );
+
+    #[inline]
+    fn radix(ctx: &Context, radix: Option<Integer>) -> Result<u32, Error> {
+        match radix {
+            None => Ok(10),
+            Some(r) => match r.to_u32() {
+                Some(r @ 2..=36) => Ok(r),
+                _ => Err(ctx.trace().error("out-of-range", Some(vector![r.into()]))),
+            },
+        }
+    }
+
+    define_typed_fn(
+        me,
+        "string->number",
+        Some(
+            "Return the number written in STRING, or `#f' if it isn't a number. Digits without a \
+                `#b', `#o' or `#x' prefix are read in RADIX, 10 by default.",
+        ),
+        |ctx: Context, s: Str, r: Option<Integer>| {
+            let r = radix(&ctx, r)?;
+            Ok(reader::parse_number(s.as_str(), r).unwrap_or(Value::Boolean(false)))
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "number->string",
+        Some(
+            "Return the digits of Z in RADIX, 10 by default. Floats can only be written in radix \
+                10.",
+        ),
+        |mut ctx: Context, z: Number, r: Option<Integer>| {
+            let r = radix(&ctx, r)?;
+            let s = match z {
+                Number::Fixnum(i) => Integer::from(i).to_string_radix(r as i32),
+                Number::Integer(i) => i.to_string_radix(r as i32),
+                Number::Rational(q) => q.to_string_radix(r as i32),
+                Number::Float(_) | Number::BigFloat(_) if r == 10 => Value::from(z).to_string(),
+                Number::Float(_) | Number::BigFloat(_) => {
+                    return Err(ctx
+                        .trace()
+                        .error("out-of-range", Some(vector![z.into(), r.into()])))
+                }
+            };
+            Ok(ctx.make_string(s))
+        },
+    );
+
+    #[inline]
+    fn random_state(ctx: &Context, state: Option<Rc<RandomState>>) -> RandomState {
+        match state {
+            Some(state) => RandomState::clone(&state),
+            None => ctx.random().clone(),
+        }
+    }
+
+    define_typed_fn(
+        me,
+        "make-random-state",
+        Some(
+            "Return a new random state seeded with SEED, or a copy of the current random state \
+                if SEED is not given.",
+        ),
+        |ctx: Context, seed: Option<Integer>| {
+            let state = match seed {
+                Some(seed) => RandomState::with_seed(&seed),
+                None => ctx.random().copy(),
+            };
+            Ok(Foreign::with_type_name(Rc::new(state), "random-state"))
+        },
+    );
+
+    define_typed_fn(me, "random-state?", Option::<&str>::None, |x: Value| {
+        Ok(x.downcast_ref::<RandomState>().is_some())
+    });
+
+    define_typed_fn(
+        me,
+        "random-seed!",
+        Some("Seed STATE, or the current random state, with the integer SEED."),
+        |ctx: Context, seed: Integer, state: Option<Rc<RandomState>>| {
+            random_state(&ctx, state).seed(&seed);
+            Ok(Value::Unspecified)
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "random-integer",
+        Some(
+            "Return a uniformly distributed integer between 0 included and N excluded, drawn \
+                from STATE or the current random state.",
+        ),
+        |ctx: Context, n: Integer, state: Option<Rc<RandomState>>| {
+            if n.cmp0() != Ordering::Greater {
+                return Err(ctx.trace().error("out-of-range", Some(vector![n.into()])));
             }
+            Ok(random_state(&ctx, state).below(n))
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "shuffle",
+        Some("Return a random permutation of LIST, drawn from STATE or the current random state."),
+        |ctx: Context, mut list: Vec<Value>, state: Option<Rc<RandomState>>| {
+            random_state(&ctx, state).shuffle(&mut list);
+            Ok(list)
+        },
+    );
+
+    define_fn(
+        me,
+        "bigfloat?",
+        Parameters::Exact(1),
+        Option::<&str>::None,
+        |_ctx, mut values| {
+            let x = values.remove(0);
+            Ok(x.is_bigfloat().into())
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "bigfloat",
+        Some(
+            "Convert the number Z to a big float of PRECISION bits, by default the precision \
+                of the current context.",
+        ),
+        |ctx: Context, z: Number, precision: Option<Integer>| {
+            let precision = match precision {
+                Some(p) => number::precision(&ctx, p)?,
+                None => ctx.precision(),
+            };
+            Ok(Float::with_val(precision, z.to_big(precision)))
         },
     );
 
+    define_typed_fn(
+        me,
+        "bigfloat-precision",
+        Some("Return the precision in bits of the big float X."),
+        |x: Float| Ok(x.prec()),
+    );
+
+    define_typed_fn(
+        me,
+        "current-precision",
+        Some(
+            "Return the precision in bits of the big floats computed in the current context, \
+                see `with-precision'.",
+        ),
+        |ctx: Context| Ok(ctx.precision()),
+    );
+
     define_macro(
         me,
         "inc",
@@ -228,3 +844,472 @@ pub fn add(me: &Environment) {
         },
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use im_rc::vector;
+    use rug::Rational;
+
+    use crate::environment::default::test_util::{eval, eval_err};
+    use crate::{number::Number, FromValue, Interpreter, Value};
+
+    #[test]
+    fn rationals() {
+        assert_eq!(eval("(/ 1 3)"), Rational::from((1, 3)).into());
+        assert_eq!(eval("(/ 6 3)"), 2.into());
+        assert_eq!(eval("(+ 1/3 2/3)"), 1.into());
+        assert_eq!(eval("(* 1/3 3 2)"), 2.into());
+        assert_eq!(eval("(- 1/2)"), Rational::from((-1, 2)).into());
+        assert_eq!(eval("(- 1 1/2)"), Rational::from((1, 2)).into());
+        assert_eq!(eval("(1+ 1/2)"), Rational::from((3, 2)).into());
+        assert_eq!(eval("(numerator 6/4)"), 3.into());
+        assert_eq!(eval("(denominator 6/4)"), 2.into());
+        assert_eq!(eval("(denominator 5)"), 1.into());
+        assert_eq!(eval("(< 1/3 1/2 1 3/2)"), true.into());
+        assert_eq!(eval("(>= 1 2/2 1/2)"), true.into());
+        assert_eq!(eval("(> 1/3 1)"), false.into());
+        assert_eq!(format!("{}", eval("(/ -4 6)")), "-2/3");
+
+        assert_eq!(eval_err("(/ 1 0)").name().as_str(), "divide-by-zero");
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(eval("(+ 1 0.5)"), 1.5.into());
+        assert_eq!(eval("(- 1/2 0.25)"), 0.25.into());
+        assert_eq!(eval("(* 2 1.5 2)"), 6.0.into());
+        assert_eq!(eval("(/ 1 2.0)"), 0.5.into());
+        assert_eq!(eval("(/ 1.0 0)"), f64::INFINITY.into());
+        assert_eq!(eval("(- 1.5)"), (-1.5).into());
+        assert_eq!(eval("(< 1 1.5 2 +inf.0)"), true.into());
+        assert_eq!(eval("(<= 0.5 1/2 1)"), true.into());
+        assert_eq!(eval("(> 1e10 9999999999)"), true.into());
+        assert_eq!(eval("(< +nan.0 1)"), false.into());
+        assert_eq!(eval("(>= +nan.0 1)"), false.into());
+        assert_eq!(eval("(float? 1.0)"), true.into());
+        assert_eq!(eval("(float? 1)"), false.into());
+
+        assert_eq!(eval("(exact->inexact 1/4)"), 0.25.into());
+        assert_eq!(eval("(exact->inexact 3)"), 3.0.into());
+        assert_eq!(eval("(inexact->exact 0.25)"), Rational::from((1, 4)).into());
+        assert_eq!(eval("(inexact->exact 2.0)"), 2.into());
+        assert_eq!(eval("(inexact->exact 7)"), 7.into());
+        assert_eq!(
+            eval_err("(inexact->exact +inf.0)").name().as_str(),
+            "out-of-range"
+        );
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(eval("(quotient -7 2)"), (-3).into());
+        assert_eq!(eval("(remainder -7 2)"), (-1).into());
+        assert_eq!(eval("(modulo -7 2)"), 1.into());
+        assert_eq!(eval("(modulo 7 -2)"), (-1).into());
+        assert_eq!(eval_err("(modulo 7 0)").name().as_str(), "divide-by-zero");
+
+        assert_eq!(eval("(abs -5)"), 5.into());
+        assert_eq!(eval("(abs -1/2)"), Rational::from((1, 2)).into());
+        assert_eq!(eval("(abs -1.5)"), 1.5.into());
+        assert_eq!(eval("(max 1 3 2)"), 3.into());
+        assert_eq!(eval("(min 1/2 1)"), Rational::from((1, 2)).into());
+        assert_eq!(eval("(max 1 2.0 3)"), 3.0.into());
+
+        assert_eq!(eval("(gcd)"), 0.into());
+        assert_eq!(eval("(gcd 12 -18 8)"), 2.into());
+        assert_eq!(eval("(lcm)"), 1.into());
+        assert_eq!(eval("(lcm 4 6)"), 12.into());
+
+        assert_eq!(eval("(expt 2 10)"), 1024.into());
+        assert_eq!(eval("(expt 2 -2)"), Rational::from((1, 4)).into());
+        assert_eq!(eval("(expt 2/3 2)"), Rational::from((4, 9)).into());
+        assert_eq!(eval("(expt 4 0.5)"), 2.0.into());
+        assert_eq!(eval("(expt 2.0 3)"), 8.0.into());
+        assert_eq!(
+            format!("{}", eval("(expt 10 30)")),
+            "1000000000000000000000000000000"
+        );
+        assert_eq!(eval_err("(expt 0 -1)").name().as_str(), "divide-by-zero");
+
+        assert_eq!(
+            eval("(exact-integer-sqrt 17)"),
+            vector![4.into(), 1.into()].into()
+        );
+        assert_eq!(
+            eval("(exact-integer-sqrt (expt 10 40))"),
+            vector![eval("(expt 10 20)"), 0.into()].into()
+        );
+        assert_eq!(
+            eval_err("(exact-integer-sqrt -1)").name().as_str(),
+            "out-of-range"
+        );
+
+        assert_eq!(eval("(even? 0)"), true.into());
+        assert_eq!(eval("(odd? -3)"), true.into());
+        assert_eq!(eval("(zero? 0.0)"), true.into());
+        assert_eq!(eval("(positive? 1/2)"), true.into());
+        assert_eq!(eval("(negative? -0.5)"), true.into());
+        assert_eq!(eval("(negative? +nan.0)"), false.into());
+    }
+
+    #[test]
+    fn bitwise() {
+        assert_eq!(eval("(logand #b1100 #b1010)"), 0b1000.into());
+        assert_eq!(eval("(logand)"), (-1).into());
+        assert_eq!(eval("(logior #b1100 #b1010)"), 0b1110.into());
+        assert_eq!(eval("(logxor #b1100 #b1010)"), 0b0110.into());
+        assert_eq!(eval("(logand -1 #xff)"), 0xff.into());
+        assert_eq!(eval("(lognot 0)"), (-1).into());
+        assert_eq!(eval("(lognot -6)"), 5.into());
+
+        assert_eq!(eval("(ash 1 100)"), eval("(expt 2 100)"));
+        assert_eq!(eval("(ash -7 -1)"), (-4).into());
+        assert_eq!(eval("(ash 7 -10000000000)"), 0.into());
+        assert_eq!(eval("(ash -7 -10000000000)"), (-1).into());
+        assert_eq!(
+            eval_err("(ash 1 10000000000)").name().as_str(),
+            "out-of-range"
+        );
+
+        assert_eq!(eval("(bit-count #b10110)"), 3.into());
+        assert_eq!(eval("(bit-count -2)"), 1.into());
+        assert_eq!(eval("(bit-set? 2 #b100)"), true.into());
+        assert_eq!(eval("(bit-set? 1 #b100)"), false.into());
+        assert_eq!(eval("(bit-set? 70 -1)"), true.into());
+        assert_eq!(eval("(integer-length #xff)"), 8.into());
+        assert_eq!(eval("(integer-length -256)"), 8.into());
+        assert_eq!(eval("(integer-length 0)"), 0.into());
+    }
+
+    #[test]
+    fn radix() {
+        assert_eq!(eval("(string->number \"42\")"), 42.into());
+        assert_eq!(
+            eval("(string->number \"-1/2\")"),
+            Rational::from((-1, 2)).into()
+        );
+        assert_eq!(eval("(string->number \"1e3\")"), 1000.0.into());
+        assert_eq!(eval("(string->number \"#xFF\")"), 255.into());
+        assert_eq!(eval("(string->number \"ff\" 16)"), 255.into());
+        assert_eq!(eval("(string->number \"#b11\" 16)"), 3.into());
+        assert_eq!(eval("(string->number \"zz\" 36)"), 1295.into());
+        assert_eq!(eval("(string->number \"12\" 2)"), false.into());
+        assert_eq!(eval("(string->number \"abc\")"), false.into());
+        assert_eq!(
+            eval_err("(string->number \"1\" 37)").name().as_str(),
+            "out-of-range"
+        );
+
+        assert_eq!(eval("(number->string 255 16)"), "ff".into());
+        assert_eq!(eval("(number->string -5 2)"), "-101".into());
+        assert_eq!(eval("(number->string 10/4)"), "5/2".into());
+        assert_eq!(eval("(number->string 1.5)"), "1.5".into());
+        assert_eq!(
+            eval_err("(number->string 1.5 2)").name().as_str(),
+            "out-of-range"
+        );
+
+        for (n, r) in [
+            ("-123456789012345678901234567890", 7),
+            ("-7/9", 3),
+            ("1/3", 36),
+        ] {
+            let code = format!("(string->number (number->string {} {}) {})", n, r, r);
+            assert_eq!(eval(&code), eval(&format!("(+ {})", n)));
+        }
+    }
+
+    #[test]
+    fn random() {
+        let interp = Interpreter::new();
+        let draw = |code: &str| interp.eval_str(code).unwrap();
+
+        draw("(random-seed! 42)");
+        let first = draw("(list (random-integer 1000000) (random-integer (expt 10 30)))");
+        draw("(random-seed! 42)");
+        let second = draw("(list (random-integer 1000000) (random-integer (expt 10 30)))");
+        assert_eq!(first, second);
+
+        for _ in 0..100 {
+            assert_eq!(draw("(< -1 (random-integer 10) 10)"), true.into());
+        }
+        assert_eq!(
+            eval_err("(random-integer 0)").name().as_str(),
+            "out-of-range"
+        );
+
+        draw("(def s (make-random-state 7))");
+        assert_eq!(draw("(random-state? s)"), true.into());
+        assert_eq!(draw("(random-state? 7)"), false.into());
+        let first = draw("(random-integer 1000000 s)");
+        draw("(random-seed! 7 s)");
+        assert_eq!(draw("(random-integer 1000000 s)"), first);
+
+        draw("(def c (make-random-state))");
+        let copy = draw("(list (random-integer 1000000 c) (random-integer 1000000))");
+        match copy {
+            Value::List(l) => assert_eq!(l[0], l[1]),
+            _ => unreachable!(),
+        }
+
+        let shuffled = draw("(shuffle '(1 2 3 4 5 6 7 8 9 10) s)");
+        let mut items: Vec<i64> = FromValue::from_value(shuffled).unwrap();
+        items.sort_unstable();
+        assert_eq!(items, (1..=10).collect::<Vec<_>>());
+        assert_eq!(draw("(shuffle '())"), Value::from(Vec::new()));
+    }
+
+    #[test]
+    fn bigfloats() {
+        assert_eq!(eval("(current-precision)"), 128.into());
+        assert_eq!(eval("(bigfloat-precision (bigfloat 1))"), 128.into());
+        assert_eq!(eval("(bigfloat-precision (bigfloat 1 300))"), 300.into());
+        assert_eq!(eval("(with-precision 256 (current-precision))"), 256.into());
+        assert_eq!(
+            eval("(bigfloat-precision (with-precision 200 (+ 1L0 1)))"),
+            200.into()
+        );
+        assert_eq!(eval("(bigfloat? (+ 1L0 1/3))"), true.into());
+        assert_eq!(eval("(bigfloat? (* 2.5 1L0))"), true.into());
+        assert_eq!(eval("(bigfloat? 1.5)"), false.into());
+        assert_eq!(eval("(= (+ 1L0 1/2) 1.5L0)"), true.into());
+        assert_eq!(eval("(< 1L0 1.5 2 2.5L0)"), true.into());
+        assert_eq!(eval("(exact->inexact 1.5L0)"), eval("(+ 1.5L0)"));
+        assert_eq!(
+            eval("(inexact->exact 1.5L0)"),
+            Rational::from((3, 2)).into()
+        );
+        assert_eq!(eval("(number->string 1.5L0)"), "1.5L0".into());
+        assert_eq!(eval("(number->string -2.25L10)"), "-2.25L10".into());
+
+        assert_eq!(eval_err("(bigfloat 1 0)").name().as_str(), "out-of-range");
+        assert_eq!(
+            eval_err("(with-precision 0 1)").name().as_str(),
+            "out-of-range"
+        );
+        assert_eq!(
+            eval_err("(with-precision 1.5 1)").name().as_str(),
+            "wrong-type-arg"
+        );
+    }
+
+    #[test]
+    fn predicates() {
+        assert_eq!(eval("(int? 1)"), true.into());
+        assert_eq!(eval("(int? 1/2)"), false.into());
+        assert_eq!(eval("(int? 1.0)"), false.into());
+        assert_eq!(eval("(int? 'a)"), false.into());
+        assert_eq!(eval("(rational? 1)"), true.into());
+        assert_eq!(eval("(rational? 1/2)"), true.into());
+        assert_eq!(eval("(rational? 0.5)"), false.into());
+        assert_eq!(eval("(rational? \"1/2\")"), false.into());
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(eval("(+)"), 0.into());
+        assert_eq!(eval("(+ 1 2 3)"), 6.into());
+        assert_eq!(eval("(*)"), 1.into());
+        assert_eq!(eval("(* 2 3 4)"), 24.into());
+        assert_eq!(eval("(- 10 1 2)"), 7.into());
+        assert_eq!(eval("(/ 60 2 3)"), 10.into());
+        assert_eq!(eval("(1+ 41)"), 42.into());
+        assert_eq!(eval("(<= 1 1 2)"), true.into());
+        assert_eq!(eval("(< 1 1)"), false.into());
+        assert_eq!(eval("(> 3 2 1)"), true.into());
+        assert_eq!(eval("(>= 1 2)"), false.into());
+        assert_eq!(eval("(let (x 1) (inc x) x)"), 2.into());
+    }
+
+    #[test]
+    fn errors() {
+        let check = |code: &str, name: &str, args: Vec<Value>| {
+            let err = eval_err(code);
+            assert_eq!(err.name().as_str(), name, "{}", code);
+            assert_eq!(err.args(), Some(args.into_iter().collect()), "{}", code);
+        };
+
+        check("(/ 1 0)", "divide-by-zero", vec![1.into(), 0.into()]);
+        check("(/ 6 2 0)", "divide-by-zero", vec![3.into(), 0.into()]);
+        check(
+            "(/ 1/2 0)",
+            "divide-by-zero",
+            vec![eval("(/ 1 2)"), 0.into()],
+        );
+        check("(quotient 1 0)", "divide-by-zero", vec![1.into(), 0.into()]);
+        check(
+            "(remainder 1 0)",
+            "divide-by-zero",
+            vec![1.into(), 0.into()],
+        );
+        check("(modulo 1 0)", "divide-by-zero", vec![1.into(), 0.into()]);
+        check("(expt 0 -2)", "divide-by-zero", vec![0.into(), (-2).into()]);
+        check(
+            "(expt 0/1 -1)",
+            "divide-by-zero",
+            vec![0.into(), (-1).into()],
+        );
+
+        check(
+            "(expt 2 (expt 2 40))",
+            "out-of-range",
+            vec![2.into(), eval("(expt 2 40)")],
+        );
+        check(
+            "(expt 8 2000000000)",
+            "out-of-range",
+            vec![8.into(), 2000000000.into()],
+        );
+        check(
+            "(ash 1 (expt 2 40))",
+            "out-of-range",
+            vec![1.into(), eval("(expt 2 40)")],
+        );
+        check(
+            "(ash 1 4294967295)",
+            "out-of-range",
+            vec![1.into(), 4294967295u32.into()],
+        );
+        check(
+            "(ash (ash 1 16777000) 1000)",
+            "out-of-range",
+            vec![eval("(ash 1 16777000)"), 1000.into()],
+        );
+        check("(bit-set? -1 1)", "out-of-range", vec![(-1).into()]);
+        check("(exact-integer-sqrt -4)", "out-of-range", vec![(-4).into()]);
+        check(
+            "(inexact->exact +nan.0)",
+            "out-of-range",
+            vec![f64::NAN.into()],
+        );
+        check("(number->string 1 1)", "out-of-range", vec![1.into()]);
+        check(
+            "(string->number \"1\" (expt 2 70))",
+            "out-of-range",
+            vec![eval("(expt 2 70)")],
+        );
+        check("(random-integer -1)", "out-of-range", vec![(-1).into()]);
+        check("(bigfloat 1 -1)", "out-of-range", vec![(-1).into()]);
+
+        check("(+ 1 \"a\")", "wrong-type-arg", vec!["a".into()]);
+        check("(+ \"a\")", "wrong-type-arg", vec!["a".into()]);
+        check("(* \"a\")", "wrong-type-arg", vec!["a".into()]);
+        check("(< 1 \"b\")", "wrong-type-arg", vec!["b".into()]);
+        check("(quotient 1/2 1)", "wrong-type-arg", vec![eval("(/ 1 2)")]);
+        check("(numerator 0.5)", "wrong-type-arg", vec![0.5.into()]);
+        check("(logand 1 1.0)", "wrong-type-arg", vec![1.0.into()]);
+        check("(gcd 1 1/2)", "wrong-type-arg", vec![eval("(/ 1 2)")]);
+        check(
+            "(bigfloat-precision 1.0)",
+            "wrong-type-arg",
+            vec![1.0.into()],
+        );
+
+        assert_eq!(eval("(/ 0.0 0)").to_string(), "+nan.0");
+        assert_eq!(eval("(expt 1 -2000000000)"), 1.into());
+        assert_eq!(eval("(quotient 0 -5)"), 0.into());
+        assert_eq!(eval("(ash 0 4294967295)"), 0.into());
+        assert_eq!(eval("(+ 1/2)"), eval("(/ 1 2)"));
+    }
+
+    #[test]
+    fn rounding() {
+        for (name, results) in [
+            ("floor", ["-4", "3", "-3", "3.0", "2", "-3.0"]),
+            ("ceiling", ["-4", "4", "-2", "4.0", "3", "-2.0"]),
+            ("truncate", ["-4", "3", "-2", "3.0", "2", "-2.0"]),
+            ("round", ["-4", "4", "-2", "4.0", "2", "-2.0"]),
+        ] {
+            for (arg, result) in ["-4", "7/2", "-5/2", "3.5", "5/2", "-2.5"]
+                .into_iter()
+                .zip(results)
+            {
+                let code = format!("({} {})", name, arg);
+                assert_eq!(eval(&code).to_string(), result, "{}", code);
+            }
+        }
+        assert_eq!(eval("(round 7/3)"), 2.into());
+        assert_eq!(eval("(round 0.5)"), 0.0.into());
+        assert_eq!(eval("(round 1.5L0)"), eval("(+ 2L0)"));
+        assert_eq!(eval("(floor +inf.0)"), f64::INFINITY.into());
+
+        assert_eq!(eval("(floor/ -7 2)"), vector![(-4).into(), 1.into()].into());
+        assert_eq!(
+            eval("(truncate/ -7 2)"),
+            vector![(-3).into(), (-1).into()].into()
+        );
+        assert_eq!(eval_err("(floor/ 1 0)").name().as_str(), "divide-by-zero");
+        assert_eq!(
+            eval_err("(truncate/ 1 0)").name().as_str(),
+            "divide-by-zero"
+        );
+
+        assert_eq!(eval("(exact 2.5)"), Rational::from((5, 2)).into());
+        assert_eq!(eval("(inexact 1/4)"), 0.25.into());
+        assert_eq!(eval("(exact? 1/2)"), true.into());
+        assert_eq!(eval("(exact? 0.5)"), false.into());
+        assert_eq!(eval("(inexact? 0.5L0)"), true.into());
+        assert_eq!(eval("(inexact? 1)"), false.into());
+        assert_eq!(eval_err("(exact? \"1\")").name().as_str(), "wrong-type-arg");
+    }
+
+    #[test]
+    fn equality() {
+        assert_eq!(eval("(= 1 1.0 1L0 2/2)"), true.into());
+        assert_eq!(eval("(= 1/2 0.5)"), true.into());
+        assert_eq!(eval("(= 1 1.5)"), false.into());
+        assert_eq!(eval("(= 0.1 1/10)"), false.into());
+        assert_eq!(eval("(= +nan.0 +nan.0)"), false.into());
+        assert_eq!(eval("(not= 1 2.0)"), true.into());
+        assert_eq!(eval("(not= 2 2.0)"), false.into());
+        assert_eq!(eval("(= \"a\" \"a\")"), true.into());
+        assert_eq!(eval_err("(= 1 \"1\")").name().as_str(), "wrong-type-arg");
+    }
+
+    #[test]
+    fn fixnums() {
+        let max = "9223372036854775807";
+        let min = "-9223372036854775808";
+
+        // Results that fit in 64 bits are computed without big integers.
+        let number = |code: &str| Number::from_value(eval(code)).unwrap();
+        assert_eq!(number("(+ 1 2)"), Number::Fixnum(3));
+        assert_eq!(number("(/ 6 3)"), Number::Fixnum(2));
+        assert_eq!(
+            eval(&format!("(+ {} 1)", max)).to_string(),
+            "9223372036854775808"
+        );
+        assert_eq!(
+            eval(&format!("(- {} 1)", min)).to_string(),
+            "-9223372036854775809"
+        );
+        assert_eq!(
+            eval(&format!("(* {} 2)", max)).to_string(),
+            "18446744073709551614"
+        );
+        assert_eq!(
+            eval(&format!("(- {})", min)).to_string(),
+            "9223372036854775808"
+        );
+        assert_eq!(
+            eval(&format!("(abs {})", min)).to_string(),
+            "9223372036854775808"
+        );
+        assert_eq!(
+            eval(&format!("(/ {} -1)", min)).to_string(),
+            "9223372036854775808"
+        );
+        assert_eq!(eval("(expt 2 63)").to_string(), "9223372036854775808");
+        assert_eq!(
+            number(&format!("(- (+ {} 1) 1)", max)),
+            Number::Fixnum(i64::MAX)
+        );
+
+        assert_eq!(eval("(+ 1 2)"), Value::Integer(3.into()));
+        assert_eq!(eval(&format!("(< {} (+ {} 1))", max, max)), true.into());
+        assert_eq!(
+            eval("(let (x 0 end (+ 0 100)) (while (< x end) (inc x)) x)"),
+            100.into()
+        );
+    }
+}
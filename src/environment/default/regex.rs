@@ -0,0 +1,184 @@
+use std::rc::Rc;
+
+use ::regex::{Captures, Regex};
+use im_rc::vector;
+
+use super::util::define_typed_fn;
+use crate::{Context, Environment, Foreign, Str, Value};
+
+/// The whole match and the groups of `caps` as a list of strings, with `#f`
+/// for the groups that didn't match.
+fn captures(ctx: &mut Context, caps: Captures) -> Value {
+    caps.iter()
+        .map(|m| match m {
+            Some(m) => ctx.make_string(m.as_str().to_string()).into(),
+            None => Value::Boolean(false),
+        })
+        .collect::<Vec<Value>>()
+        .into()
+}
+
+pub fn add(me: &Environment) {
+    define_typed_fn(
+        me,
+        "regex-compile",
+        Some("Return the regular expression written in PATTERN."),
+        |ctx: Context, pattern: Str| match Regex::new(pattern.as_str()) {
+            Ok(re) => Ok(Foreign::with_type_name(Rc::new(re), "regex")),
+            Err(err) => Err(ctx.trace().error(
+                "regex-error",
+                Some(vector![pattern.into(), err.to_string().into()]),
+            )),
+        },
+    );
+
+    define_typed_fn(me, "regex?", Option::<&str>::None, |x: Value| {
+        Ok(x.downcast_ref::<Regex>().is_some())
+    });
+
+    define_typed_fn(
+        me,
+        "regex-match",
+        Some(
+            "Return the first match of REGEX in STRING as the list of the matched text and of its \
+                groups, `#f' for the groups that didn't match. Return `#f' if there is no match.",
+        ),
+        |mut ctx: Context, re: Rc<Regex>, s: Str| {
+            Ok(match re.captures(s.as_str()) {
+                Some(caps) => captures(&mut ctx, caps),
+                None => Value::Boolean(false),
+            })
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "regex-match-all",
+        Some(
+            "Return the list of the matches of REGEX in STRING that don't overlap, each one as \
+                returned by `regex-match'.",
+        ),
+        |mut ctx: Context, re: Rc<Regex>, s: Str| {
+            Ok(re
+                .captures_iter(s.as_str())
+                .map(|caps| captures(&mut ctx, caps))
+                .collect::<Vec<Value>>())
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "regex-replace",
+        Some(
+            "Return STRING with the first COUNT matches of REGEX, all of them if COUNT is not \
+                given or 0, replaced by REPLACEMENT. `$1' or `${name}' in REPLACEMENT stand for \
+                the text matched by a group.",
+        ),
+        |mut ctx: Context, re: Rc<Regex>, s: Str, replacement: Str, count: Option<usize>| {
+            let res = re.replacen(s.as_str(), count.unwrap_or(0), replacement.as_str());
+            Ok(ctx.make_string(res.into_owned()))
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "regex-split",
+        Some("Return the list of the parts of STRING separated by the matches of REGEX."),
+        |mut ctx: Context, re: Rc<Regex>, s: Str| {
+            Ok(re
+                .split(s.as_str())
+                .map(|part| ctx.make_string(part.to_string()).into())
+                .collect::<Vec<Value>>())
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use im_rc::vector;
+
+    use crate::environment::default::test_util::{eval, eval_err};
+    use crate::Value;
+
+    fn strings(parts: &[&'static str]) -> Value {
+        parts
+            .iter()
+            .map(|&s| s.into())
+            .collect::<Vec<Value>>()
+            .into()
+    }
+
+    #[test]
+    fn compile() {
+        assert_eq!(eval(r#"(regex? (regex-compile "a+"))"#), true.into());
+        assert_eq!(eval(r#"(regex? "a+")"#), false.into());
+
+        let err = eval_err(r#"(regex-compile "a(b")"#);
+        assert_eq!(err.name().as_str(), "regex-error");
+        assert_eq!(
+            err.args().and_then(|args| args.head().cloned()),
+            Some("a(b".into())
+        );
+        assert_eq!(
+            eval_err(r#"(regex-match "a" "a")"#).name().as_str(),
+            "wrong-type-arg"
+        );
+    }
+
+    #[test]
+    fn matches() {
+        assert_eq!(
+            eval(
+                r#"(regex-match (regex-compile "(\\w+)@(\\w+)(\\.org)?") "mail bob@example now")"#
+            ),
+            Value::from(vector![
+                "bob@example".into(),
+                "bob".into(),
+                "example".into(),
+                false.into()
+            ])
+        );
+        assert_eq!(
+            eval(r#"(regex-match (regex-compile "\\d") "none")"#),
+            false.into()
+        );
+        assert_eq!(
+            eval(r#"(regex-match-all (regex-compile "(\\w)=(\\d+)") "a=1, b=22 c=x d=333")"#),
+            Value::from(vec![
+                strings(&["a=1", "a", "1"]),
+                strings(&["b=22", "b", "22"]),
+                strings(&["d=333", "d", "333"]),
+            ])
+        );
+        assert_eq!(
+            eval(r#"(regex-match-all (regex-compile "z") "abc")"#),
+            Value::from(Vec::<Value>::new())
+        );
+    }
+
+    #[test]
+    fn replace_and_split() {
+        assert_eq!(
+            eval(r#"(regex-replace (regex-compile "(\\d+)") "a1 b22 c333" "<$1>")"#),
+            "a<1> b<22> c<333>".into()
+        );
+        assert_eq!(
+            eval(r#"(regex-replace (regex-compile "\\d") "1234" "x" 2)"#),
+            "xx34".into()
+        );
+        assert_eq!(
+            eval(
+                r#"(regex-replace (regex-compile "(?P<y>\\d+)-(?P<m>\\d+)") "2023-04" "${m}/${y}")"#
+            ),
+            "04/2023".into()
+        );
+        assert_eq!(
+            eval(r#"(regex-split (regex-compile ",\\s*") "a, b,c,,  d")"#),
+            strings(&["a", "b", "c", "", "d"])
+        );
+        assert_eq!(
+            eval(r#"(regex-split (regex-compile "\\s+") "λ μ")"#),
+            strings(&["λ", "μ"])
+        );
+    }
+}
@@ -1,18 +1,20 @@
 use std::num::NonZeroUsize;
 
-use super::util::define_fn;
-use crate::{proc::Parameters, Context, Environment, Error, Str, Symbol, Value};
+use im_rc::vector;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::util::{define_fn, define_typed_fn};
+use crate::{
+    parser::reader::util::CountChars, proc::Parameters, Context, Environment, Error, Str, Symbol,
+    Value,
+};
 
 pub fn add(me: &Environment) {
-    define_fn(
+    define_typed_fn(
         me,
         "string->sym",
-        Parameters::Exact(1),
         Some("Return the symbol whose name is STRING."),
-        |ctx, mut values| match values.remove(0) {
-            Value::String(s) => Ok(Value::Symbol(Symbol::Name(s))),
-            _ => Err(ctx.trace().error("wrong-type-arg", None)),
-        },
+        |s: Str| Ok(Symbol::Name(s)),
     );
 
     define_fn(
@@ -40,26 +42,16 @@ pub fn add(me: &Environment) {
         },
     );
 
-    define_fn(
-        me,
-        "string-length",
-        Parameters::Exact(1),
-        Option::<&str>::None,
-        |ctx, mut values| {
-            if let Value::String(s) = values.remove(0) {
-                Ok(s.len().into())
-            } else {
-                Err(ctx.trace().error("wrong-type-arg", None))
-            }
-        },
-    );
+    define_typed_fn(me, "string-length", Option::<&str>::None, |s: Str| {
+        Ok(s.len())
+    });
 
     define_fn(
         me,
         "substring",
         Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(3) }),
         Option::<&str>::None,
-        |mut ctx, mut values| {
+        |ctx, mut values| {
             #[inline(always)]
             fn string(ctx: &Context, v: Value) -> Result<Str, Error> {
                 if let Value::String(str) = v {
@@ -71,15 +63,11 @@ pub fn add(me: &Environment) {
 
             #[inline]
             fn index(ctx: &Context, v: Value) -> Result<usize, Error> {
-                if let Value::Integer(i) = v {
-                    if let Some(i) = i.to_usize() {
-                        Ok(i)
-                    } else {
-                        Err(ctx.trace().error("out-of-range", None))
-                    }
-                } else {
-                    Err(ctx.trace().error("wrong-type-arg", None))
-                }
+                let i = match v {
+                    Value::Integer(i) => i.to_usize(),
+                    _ => return Err(ctx.trace().error("wrong-type-arg", None)),
+                };
+                i.ok_or_else(|| ctx.trace().error("out-of-range", None))
             }
 
             let (s, start, len) = match values.len() {
@@ -96,9 +84,224 @@ pub fn add(me: &Environment) {
                 _ => return Err(ctx.trace().error("wrong-number-of-args", None)),
             };
 
-            s.substring_in_context(&mut ctx, start, len)
+            s.substring(start, len)
                 .map(Into::into)
                 .ok_or_else(|| ctx.trace().error("out-of-range", None))
         },
     );
+
+    define_fn(
+        me,
+        "string-append",
+        Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(1) }),
+        Some("Return a string made of the characters of all the argument strings, in order."),
+        |mut ctx, values| {
+            let mut res = String::new();
+            for v in values {
+                match v {
+                    Value::String(s) => res.push_str(s.as_str()),
+                    v => return Err(ctx.trace().error("wrong-type-arg", Some(vector![v]))),
+                }
+            }
+            Ok(ctx.make_string(res).into())
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "string-upcase",
+        Some("Return STRING with all its characters in upper case."),
+        |mut ctx: Context, s: Str| Ok(ctx.make_string(s.as_str().to_uppercase())),
+    );
+
+    define_typed_fn(
+        me,
+        "string-downcase",
+        Some("Return STRING with all its characters in lower case."),
+        |mut ctx: Context, s: Str| Ok(ctx.make_string(s.as_str().to_lowercase())),
+    );
+
+    define_typed_fn(
+        me,
+        "string-split",
+        Some(
+            "Return the list of the parts of STRING separated by SEPARATOR, or by whitespace if \
+                SEPARATOR is not given.",
+        ),
+        |mut ctx: Context, s: Str, sep: Option<Str>| {
+            let parts: Vec<&str> = match sep {
+                None => s.as_str().split_whitespace().collect(),
+                Some(sep) if sep.is_empty() => {
+                    return Err(ctx.trace().error("out-of-range", Some(vector![sep.into()])))
+                }
+                Some(ref sep) => s.as_str().split(sep.as_str()).collect(),
+            };
+            Ok(parts
+                .into_iter()
+                .map(|part| ctx.make_string(part.to_string()).into())
+                .collect::<Vec<Value>>())
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "string-join",
+        Some("Return the strings in LIST joined by SEPARATOR, by default a space."),
+        |mut ctx: Context, parts: Vec<Str>, sep: Option<Str>| {
+            let sep = sep.as_ref().map_or(" ", Str::as_str);
+            let parts: Vec<&str> = parts.iter().map(Str::as_str).collect();
+            Ok(ctx.make_string(parts.join(sep)))
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "string-trim",
+        Some(
+            "Return STRING without its leading and trailing whitespace, or without the leading \
+                and trailing characters found in CHARS if given.",
+        ),
+        |mut ctx: Context, s: Str, chars: Option<Str>| {
+            let trimmed = match chars {
+                None => s.as_str().trim(),
+                Some(ref chars) => s.as_str().trim_matches(|c| chars.as_str().contains(c)),
+            };
+            Ok(ctx.make_string(trimmed.to_string()))
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "string-index",
+        Some("Return the index of the first occurrence of CHAR in STRING, or `#f'."),
+        |s: Str, c: char| Ok(char_index(&s, s.as_str().find(c))),
+    );
+
+    define_typed_fn(
+        me,
+        "string-contains",
+        Some("Return the index of the first occurrence of NEEDLE in STRING, or `#f'."),
+        |s: Str, needle: Str| Ok(char_index(&s, s.as_str().find(needle.as_str()))),
+    );
+
+    define_typed_fn(
+        me,
+        "string-prefix?",
+        Some("Return `#t' if STRING starts with PREFIX."),
+        |prefix: Str, s: Str| Ok(s.as_str().starts_with(prefix.as_str())),
+    );
+
+    define_typed_fn(
+        me,
+        "string-suffix?",
+        Some("Return `#t' if STRING ends with SUFFIX."),
+        |suffix: Str, s: Str| Ok(s.as_str().ends_with(suffix.as_str())),
+    );
+
+    define_typed_fn(
+        me,
+        "string-replace",
+        Some("Return STRING with every occurrence of FROM replaced by TO."),
+        |mut ctx: Context, s: Str, from: Str, to: Str| {
+            if from.is_empty() {
+                return Err(ctx
+                    .trace()
+                    .error("out-of-range", Some(vector![from.into()])));
+            }
+            Ok(ctx.make_string(s.as_str().replace(from.as_str(), to.as_str())))
+        },
+    );
+
+    define_typed_fn(
+        me,
+        "string-reverse",
+        Some(
+            "Return STRING with its characters in reverse order. Combining characters stay \
+                attached to the character they modify.",
+        ),
+        |mut ctx: Context, s: Str| {
+            let reversed: String = s.as_str().graphemes(true).rev().collect();
+            Ok(ctx.make_string(reversed))
+        },
+    );
+}
+
+/// The index in characters of the byte offset `offset` of `s`, `#f` if
+/// `offset` is `None`.
+#[inline]
+fn char_index(s: &Str, offset: Option<usize>) -> Value {
+    match offset {
+        Some(offset) => s.as_str()[..offset].count_chars().into(),
+        None => false.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::environment::default::test_util::{eval, eval_err};
+    use crate::Value;
+
+    #[test]
+    fn append_and_case() {
+        assert_eq!(eval(r#"(string-append)"#), "".into());
+        assert_eq!(
+            eval(r#"(string-append "ciao" ", " "mondo")"#),
+            "ciao, mondo".into()
+        );
+        assert_eq!(
+            eval_err(r#"(string-append "a" 1)"#).name().as_str(),
+            "wrong-type-arg"
+        );
+        assert_eq!(eval(r#"(string-upcase "straße")"#), "STRASSE".into());
+        assert_eq!(eval(r#"(string-downcase "ÀÉÎ")"#), "àéî".into());
+    }
+
+    #[test]
+    fn split_join_trim() {
+        assert_eq!(
+            eval(r#"(string-split "  a b\tc ")"#),
+            Value::from(vec!["a".into(), "b".into(), "c".into()])
+        );
+        assert_eq!(
+            eval(r#"(string-split "a,,b" ",")"#),
+            Value::from(vec!["a".into(), "".into(), "b".into()])
+        );
+        assert_eq!(
+            eval_err(r#"(string-split "a" "")"#).name().as_str(),
+            "out-of-range"
+        );
+        assert_eq!(eval(r#"(string-join '("a" "b" "c"))"#), "a b c".into());
+        assert_eq!(eval(r#"(string-join '("a" "b") "→")"#), "a→b".into());
+        assert_eq!(eval(r#"(string-join '())"#), "".into());
+        assert_eq!(
+            eval_err(r#"(string-join '("a" 1))"#).name().as_str(),
+            "wrong-type-arg"
+        );
+        assert_eq!(eval(r#"(string-trim " \n ciao\t")"#), "ciao".into());
+        assert_eq!(eval(r#"(string-trim "--ciao-" "-")"#), "ciao".into());
+    }
+
+    #[test]
+    fn search() {
+        assert_eq!(eval(r#"(string-index "ααβ" #\β)"#), 2.into());
+        assert_eq!(eval(r#"(string-index "abc" #\z)"#), false.into());
+        assert_eq!(eval(r#"(string-contains "añob" "ob")"#), 2.into());
+        assert_eq!(eval(r#"(string-contains "abc" "")"#), 0.into());
+        assert_eq!(eval(r#"(string-contains "abc" "d")"#), false.into());
+        assert_eq!(eval(r#"(string-prefix? "ci" "ciao")"#), true.into());
+        assert_eq!(eval(r#"(string-prefix? "ciao" "ci")"#), false.into());
+        assert_eq!(eval(r#"(string-suffix? "ao" "ciao")"#), true.into());
+    }
+
+    #[test]
+    fn replace_and_reverse() {
+        assert_eq!(eval(r#"(string-replace "a-b-c" "-" "→")"#), "a→b→c".into());
+        assert_eq!(
+            eval_err(r#"(string-replace "abc" "" "x")"#).name().as_str(),
+            "out-of-range"
+        );
+        assert_eq!(eval(r#"(string-reverse "abc")"#), "cba".into());
+        assert_eq!(eval("(string-reverse \"ae\u{301}o\")"), "oe\u{301}a".into());
+        assert_eq!(eval(r#"(string-reverse "")"#), "".into());
+    }
 }
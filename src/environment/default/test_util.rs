@@ -0,0 +1,15 @@
+use crate::{Error, EvalError, Interpreter, Value};
+
+/// Evaluates `code` in a new interpreter, panicking on errors.
+pub fn eval(code: &str) -> Value {
+    Interpreter::new().eval_str(code).unwrap()
+}
+
+/// Evaluates `code` in a new interpreter and returns the runtime error it
+/// must fail with.
+pub fn eval_err(code: &str) -> Error {
+    match Interpreter::new().eval_str(code) {
+        Err(EvalError::Runtime(err)) => err,
+        res => panic!("expected an error from {}, got {:?}", code, res),
+    }
+}
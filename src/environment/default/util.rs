@@ -2,7 +2,10 @@ use std::num::NonZeroUsize;
 
 use im_rc::Vector;
 
-use crate::{proc::Parameters, Context, Environment, Error, Proc, Str, Symbol, Value};
+use crate::{
+    proc::{NativeFn, Parameters},
+    Context, Environment, Error, Proc, Str, Symbol, Value,
+};
 
 pub fn define_fn<F, S1, S2>(
     env: &Environment,
@@ -21,6 +24,18 @@ pub fn define_fn<F, S1, S2>(
     env.define(Symbol::Name(name), Value::Fn(lambda));
 }
 
+pub fn define_typed_fn<Args, F, S1, S2>(env: &Environment, name: S1, doc: Option<S2>, f: F)
+where
+    F: NativeFn<Args>,
+    S1: Into<Str>,
+    S2: Into<Str>,
+{
+    let mut lambda = Proc::from_fn(doc.map(|s| s.into()), f);
+    let name: Str = name.into();
+    lambda.set_name(name.clone());
+    env.define(Symbol::Name(name), Value::Fn(lambda));
+}
+
 #[allow(dead_code)]
 pub fn define_macro<F, S1, S2>(
     env: &Environment,
@@ -1,4 +1,5 @@
 mod bag;
+mod builder;
 mod default;
 mod proc;
 
@@ -14,6 +15,7 @@ use std::{
 use crate::{Symbol, Value, Var};
 
 pub use bag::Bag;
+pub use builder::{Capability, EnvironmentBuilder};
 
 struct Repr {
     parent: Option<Rc<RefCell<Repr>>>,
@@ -131,6 +133,15 @@ impl Environment {
         RefCell::borrow_mut(&*self.0).define(key, value)
     }
 
+    /// The variables defined directly in this environment.
+    pub fn bindings(&self) -> Vec<(Symbol, Var)> {
+        RefCell::borrow(&*self.0)
+            .bag
+            .iter()
+            .map(|(name, var)| (name.clone(), var.clone()))
+            .collect()
+    }
+
     #[inline]
     pub fn toplevel(&self) -> Self {
         Self(unsafe { toplevel(&self.0) })
@@ -221,8 +232,10 @@ mod tests {
                 ctx.clone(),
                 vector![Value::String("ciao".into())]
             )
-            .unwrap(),
-            Value::String("ciao".into())
+            .unwrap_err()
+            .name()
+            .as_str(),
+            "wrong-type-arg"
         );
 
         assert_eq!(
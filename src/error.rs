@@ -1,8 +1,8 @@
-use std::fmt;
+use std::{fmt, rc::Rc};
 
 use im_rc::Vector;
 
-use crate::{BackTrace, Str, Value};
+use crate::{parser::SourceSpan, util::print_list_debug, BackTrace, Str, Value};
 
 #[derive(Debug, Clone)]
 pub struct Error {
@@ -31,10 +31,98 @@ impl Error {
     pub fn backtrace(&self) -> BackTrace {
         self.trace.clone()
     }
+
+    /// The position of the innermost form that raised this error, if known.
+    pub fn span(&self) -> Option<Rc<SourceSpan>> {
+        self.trace
+            .clone()
+            .into_iter()
+            .find_map(|frame| frame.span())
+    }
 }
 
 impl fmt::Display for Error {
-    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        todo!()
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(span) = self.span() {
+            write!(f, "{}: ", span)?;
+        }
+
+        fmt::Display::fmt(&self.name, f)?;
+
+        if let Some(ref args) = self.args {
+            write!(f, " ")?;
+            print_list_debug(f, args.iter(), "(", ")")?;
+        }
+
+        for frame in self.trace.clone() {
+            write!(f, "\n  at {}", frame)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use im_rc::vector;
+
+    use crate::{
+        parser::parse, proc::Parameters, BackTrace, Context, Environment, Interpreter, Program,
+        Symbol, TraceFrame, Value,
+    };
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            format!("{}", BackTrace::new().error("syntax-error", None)),
+            "syntax-error\n  at <main>"
+        );
+
+        let trace = BackTrace::new().with_frame(TraceFrame::named(0, Symbol::Name("test".into())));
+        let err = trace.error(
+            "unbound-variable",
+            Some(vector![
+                Value::Symbol(Symbol::Name("x".into())),
+                "ciao".into()
+            ]),
+        );
+        assert_eq!(
+            format!("{}", err),
+            "unbound-variable (x \"ciao\")\n  at test\n  at <main>"
+        );
+    }
+
+    #[test]
+    fn display_span() {
+        let code = parse("(def x 1)\n\n(+ x\n   (- y))").unwrap();
+        let err = Program::new(code)
+            .eval(Context::new(), Environment::default())
+            .unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "<unknown>:4:4: unbound-variable (y)\n  at <main> (<unknown>:4:4)"
+        );
+    }
+
+    #[test]
+    fn span_of_native_error() {
+        let interp = Interpreter::new();
+        interp.define_native(
+            "fail",
+            Parameters::Exact(0),
+            Option::<&str>::None,
+            |ctx, _| Err(ctx.trace().error("failed", None)),
+        );
+
+        // The frame of `fail` has no position, the one of the form calling
+        // it does.
+        let err = interp.eval_str("(def x 1)\n  (fail)").unwrap_err();
+        assert_eq!(
+            format!("{}", err).lines().next(),
+            Some("<unknown>:2:3: failed")
+        );
     }
 }
@@ -61,6 +61,9 @@ where
         | Value::Boolean(_)
         | Value::Character(_)
         | Value::Integer(_)
+        | Value::Rational(_)
+        | Value::Float(_)
+        | Value::BigFloat(_)
         | Value::String(_)
         | Value::Fn(_)
         | Value::Macro(_)
@@ -68,12 +71,19 @@ where
         | Value::Environment(_)
         | Value::Error(_)
         | Value::BackTrace(_)
-        | Value::Frame(_) => Ok(me.into()),
+        | Value::Frame(_)
+        | Value::Foreign(_) => Ok(me.into()),
         Value::Symbol(sym) => env.get(sym.clone()).map(|v| v.get().into()).ok_or_else(|| {
             ctx.trace()
                 .error("unbound-variable", Some(vector![Value::Symbol(sym)]))
         }),
-        Value::List(mut l) => {
+        Value::List(l) => {
+            let ctx = match l.span() {
+                Some(span) => ctx.with_span(span),
+                None => ctx,
+            };
+            let mut l = l.into_inner();
+
             if let Some(first) = l.pop_front() {
                 if let Value::Symbol(Symbol::Name(ref s)) = first {
                     if let Some(res) = special::transform_fn(
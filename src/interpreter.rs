@@ -0,0 +1,228 @@
+use std::{borrow::Borrow, io, num::NonZeroUsize, path::Path, string::FromUtf8Error};
+
+use im_rc::Vector;
+use thiserror::Error;
+
+use crate::{
+    eval,
+    parser::{self, parse_from_file_with_cache, parse_with_cache, FileParseError},
+    proc::{NativeFn, Parameters},
+    Context, Environment, Error, Proc, Program, Str, StrCache, Symbol, Value,
+};
+
+#[derive(Error, Debug)]
+pub enum EvalError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Encoding(#[from] FromUtf8Error),
+    #[error(transparent)]
+    Parse(#[from] parser::Error),
+    #[error(transparent)]
+    Runtime(#[from] Error),
+}
+
+impl From<FileParseError> for EvalError {
+    fn from(value: FileParseError) -> Self {
+        match value {
+            FileParseError::Io(err) => Self::Io(err),
+            FileParseError::Encoding(err) => Self::Encoding(err),
+            FileParseError::Parse(err) => Self::Parse(err),
+        }
+    }
+}
+
+/// An interpreter session: every piece of code evaluated through it shares
+/// the same string cache, gensym counter and toplevel environment.
+#[derive(Clone)]
+pub struct Interpreter {
+    cache: StrCache,
+    ctx: Context,
+    env: Environment,
+}
+
+impl Interpreter {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_environment(Environment::default())
+    }
+
+    pub fn with_environment(env: Environment) -> Self {
+        let cache = StrCache::new();
+
+        Self {
+            ctx: Context::with_cache(cache.clone()),
+            cache,
+            env,
+        }
+    }
+
+    #[inline]
+    pub fn context(&self) -> Context {
+        self.ctx.clone()
+    }
+
+    #[inline]
+    pub fn environment(&self) -> Environment {
+        self.env.clone()
+    }
+
+    #[inline]
+    pub fn parse(&self, code: &str) -> Result<Vector<Value>, parser::Error> {
+        parse_with_cache(code, self.cache.clone())
+    }
+
+    /// Evaluates already parsed forms, returning the value of the last one.
+    pub fn eval(&self, code: Vector<Value>) -> Result<Value, Error> {
+        if code.is_empty() {
+            Ok(Value::Unspecified)
+        } else {
+            Program::new(code).eval(self.ctx.clone(), self.env.clone())
+        }
+    }
+
+    pub fn eval_str(&self, code: &str) -> Result<Value, EvalError> {
+        let code = self.parse(code)?;
+        Ok(self.eval(code)?)
+    }
+
+    pub fn eval_file<P: AsRef<Path>>(&self, path: P) -> Result<Value, EvalError> {
+        let code = parse_from_file_with_cache(path.as_ref(), self.cache.clone())?;
+        Ok(self.eval(code)?)
+    }
+
+    /// Calls the procedure bound to `name` in the toplevel environment.
+    pub fn call<S>(&self, name: S, args: Vector<Value>) -> Result<Value, Error>
+    where
+        S: Borrow<str> + Into<Str>,
+    {
+        let name = self.symbol(name);
+
+        match self.env.get(&name) {
+            Some(var) => eval::apply(var.get(), self.ctx.clone(), args),
+            None => Err(self
+                .ctx
+                .trace()
+                .error("unbound-variable", Some(Vector::unit(name.into())))),
+        }
+    }
+
+    pub fn define_value<S, V>(&self, name: S, value: V)
+    where
+        S: Borrow<str> + Into<Str>,
+        V: Into<Value>,
+    {
+        self.env.define(self.symbol(name), value.into());
+    }
+
+    pub fn define_native<S1, S2, F>(
+        &self,
+        name: S1,
+        parameters: Parameters<usize, NonZeroUsize>,
+        doc: Option<S2>,
+        f: F,
+    ) where
+        S1: Borrow<str> + Into<Str>,
+        S2: Into<Str>,
+        F: (Fn(Context, Vector<Value>) -> Result<Value, Error>) + 'static,
+    {
+        let name = self.symbol(name);
+        let mut lambda = Proc::from_native(parameters, doc.map(|s| s.into()), f);
+        lambda.set_name(name.clone());
+        self.env.define(name, Value::Fn(lambda));
+    }
+
+    /// Defines a native procedure from a typed Rust function, see
+    /// [`NativeFn`].
+    pub fn define_fn<Args, S1, S2, F>(&self, name: S1, doc: Option<S2>, f: F)
+    where
+        S1: Borrow<str> + Into<Str>,
+        S2: Into<Str>,
+        F: NativeFn<Args>,
+    {
+        let name = self.symbol(name);
+        let mut lambda = Proc::from_fn(doc.map(|s| s.into()), f);
+        lambda.set_name(name.clone());
+        self.env.define(name, Value::Fn(lambda));
+    }
+
+    #[inline]
+    fn symbol<S: Borrow<str> + Into<Str>>(&self, name: S) -> Symbol {
+        let mut cache = self.cache.clone();
+        Symbol::Name(cache.get(name))
+    }
+}
+
+impl Default for Interpreter {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use im_rc::vector;
+
+    use super::{EvalError, Interpreter};
+    use crate::{proc::Parameters, Str, Value};
+
+    #[test]
+    fn eval_str() {
+        let interp = Interpreter::new();
+
+        assert_eq!(interp.eval_str("(+ 1 2)").unwrap(), 3.into());
+        assert_eq!(interp.eval_str("").unwrap(), Value::Unspecified);
+        assert!(matches!(interp.eval_str("(+ 1"), Err(EvalError::Parse(_))));
+        assert!(matches!(
+            interp.eval_str("(+ 1 x)"),
+            Err(EvalError::Runtime(_))
+        ));
+
+        interp.eval_str("(def x 41)").unwrap();
+        assert_eq!(interp.eval_str("(+ 1 x)").unwrap(), 42.into());
+    }
+
+    #[test]
+    fn call_and_define() {
+        let interp = Interpreter::new();
+
+        interp.define_value("x", 2);
+        interp.define_native(
+            "double",
+            Parameters::Exact(1),
+            Option::<&str>::None,
+            |ctx, values| match values[0] {
+                Value::Integer(ref i) => Ok(Value::Integer(i.clone() * 2)),
+                _ => Err(ctx.trace().error("wrong-type-arg", None)),
+            },
+        );
+
+        interp.define_fn("repeat", Some("Repeat S N times."), |s: Str, n: usize| {
+            Ok(s.as_str().repeat(n))
+        });
+
+        assert_eq!(interp.eval_str("(double x)").unwrap(), 4.into());
+        // Literals and arithmetic results reach host code as `Value::Integer`.
+        assert_eq!(interp.eval_str("(double 21)").unwrap(), 42.into());
+        assert_eq!(interp.eval_str("(double (+ x 1))").unwrap(), 6.into());
+        assert_eq!(interp.eval_str("(repeat \"ab\" x)").unwrap(), "abab".into());
+        assert_eq!(
+            interp.call("double", vector![21.into()]).unwrap(),
+            42.into()
+        );
+        assert_eq!(
+            interp.call("nope", vector![]).unwrap_err().name().as_str(),
+            "unbound-variable"
+        );
+    }
+
+    #[test]
+    fn gensym() {
+        let interp = Interpreter::new();
+
+        let a = interp.eval_str("(gensym)").unwrap();
+        let b = interp.eval_str("(gensym)").unwrap();
+        assert_ne!(a, b);
+    }
+}
@@ -3,9 +3,15 @@ mod context;
 mod environment;
 mod error;
 pub(crate) mod eval;
+mod interpreter;
+mod number;
 pub mod parser;
+mod port;
 pub mod proc;
 mod program;
+mod random;
+#[cfg(feature = "serde")]
+pub mod serde;
 mod special;
 mod str_cache;
 mod string;
@@ -16,12 +22,15 @@ mod var;
 
 pub use backtrace::*;
 pub use context::Context;
-pub use environment::Environment;
+pub use environment::{Capability, Environment, EnvironmentBuilder};
 pub use error::Error;
+pub use interpreter::{EvalError, Interpreter};
+pub use port::OutputPort;
 pub use proc::Proc;
 pub use program::Program;
+pub use random::RandomState;
 pub use str_cache::StrCache;
 pub use string::*;
 pub use symbol::Symbol;
-pub use value::Value;
+pub use value::{Foreign, FromValue, IntoValue, List, Value};
 pub use var::Var;
@@ -1,34 +1,329 @@
-use lispizzle::{
-    parser::{parse_from_file_with_cache, FileParseError},
-    Context, Environment, Program, StrCache,
+use std::{
+    env, fmt,
+    io::{self, BufRead, Read, Write},
+    path::PathBuf,
+    process::ExitCode,
 };
 
+use im_rc::{vector, Vector};
+use lispizzle::{Error, EvalError, Interpreter, Str, Value};
+
 extern crate lispizzle;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cache = StrCache::new();
+const USAGE: &str = "\
+Usage: lispizzle [OPTIONS] [SCRIPT | -] [ARGS...]
+       lispizzle repl
+
+Evaluate SCRIPT (or the standard input if SCRIPT is `-'). Every argument
+following SCRIPT is passed to it in the `argv' variable.
+
+The `repl' command starts an interactive session instead.
+
+Options:
+  -e EXPR     evaluate EXPR, can be given multiple times
+  -h, --help  print this help and exit
+  --          stop option processing, the next argument is SCRIPT";
+
+#[derive(Debug, PartialEq)]
+enum Source {
+    Expr(String),
+    File(PathBuf),
+    Stdin,
+}
 
-    let code = match parse_from_file_with_cache("examples/macros.zle".as_ref(), cache.clone()) {
-        Ok(x) => x,
-        Err(FileParseError::Parse(err)) => {
-            println!("{}", err);
-            return Ok(());
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expr(_) => write!(f, "<expr>"),
+            Self::File(path) => fmt::Display::fmt(&path.display(), f),
+            Self::Stdin => write!(f, "<stdin>"),
         }
-        e => e?,
-    };
+    }
+}
 
-    let prog = Program::new(code);
+#[derive(Debug, PartialEq)]
+struct Args {
+    sources: Vec<Source>,
+    argv: Vec<String>,
+}
 
-    match prog.eval(Context::with_cache(cache), Environment::default()) {
-        Ok(_) => (),
-        Err(err) => {
-            println!("{:#?}", err);
-            for frame in err.backtrace().into_iter() {
-                println!("{:?}", frame);
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run(Args),
+    Repl,
+    Help,
+}
+
+fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.peekable();
+    let mut sources = Vec::new();
+    let mut argv = Vec::new();
+
+    if args.next_if(|arg| arg == "repl").is_some() {
+        return if args.next().is_none() {
+            Ok(Command::Repl)
+        } else {
+            Err("too many arguments for `repl'".to_string())
+        };
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-e" => match args.next() {
+                Some(expr) => sources.push(Source::Expr(expr)),
+                None => return Err("option `-e' requires an argument".to_string()),
+            },
+            "--" => {
+                if let Some(script) = args.next() {
+                    argv.push(script);
+                }
+                break;
+            }
+            "-" => {
+                argv.push(arg);
+                break;
             }
-            return Ok(());
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}'", arg)),
+            _ => {
+                argv.push(arg);
+                break;
+            }
+        }
+    }
+
+    if let Some(script) = argv.first() {
+        if script == "-" {
+            sources.push(Source::Stdin);
+        } else {
+            sources.push(Source::File(script.into()));
         }
     }
+    argv.extend(args);
 
-    Ok(())
+    if sources.is_empty() {
+        Err("nothing to evaluate".to_string())
+    } else {
+        Ok(Command::Run(Args { sources, argv }))
+    }
+}
+
+fn report(err: &Error) {
+    eprintln!("error: {}", err);
+}
+
+fn eval(interp: &Interpreter, source: &Source) -> Result<Value, EvalError> {
+    match source {
+        Source::Expr(code) => interp.eval_str(code),
+        Source::File(path) => interp.eval_file(path),
+        Source::Stdin => {
+            let mut code = String::new();
+            io::stdin().read_to_string(&mut code)?;
+            interp.eval_str(&code)
+        }
+    }
+}
+
+/// An interpreter where `argv` is bound to the list of `argv`.
+fn interpreter(argv: Vec<String>) -> Interpreter {
+    let interp = Interpreter::new();
+
+    interp.define_value(
+        "argv",
+        argv.into_iter()
+            .map(|arg| Value::from(Str::from(arg)))
+            .collect::<Vector<_>>(),
+    );
+    interp
+}
+
+fn run(args: Args) -> ExitCode {
+    let interp = interpreter(args.argv);
+
+    for source in args.sources.iter() {
+        match eval(&interp, source) {
+            Ok(_) => (),
+            Err(EvalError::Parse(err)) => {
+                eprint!("{}", err);
+                return ExitCode::FAILURE;
+            }
+            Err(EvalError::Runtime(err)) => {
+                report(&err);
+                return ExitCode::FAILURE;
+            }
+            Err(err) => {
+                eprintln!("{}: {}", source, err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn repl() -> ExitCode {
+    let interp = Interpreter::new();
+    let mut stdin = io::stdin().lock();
+    let mut buffer = String::new();
+
+    loop {
+        print!(
+            "{}",
+            if buffer.is_empty() {
+                "lispizzle> "
+            } else {
+                "... "
+            }
+        );
+        _ = io::stdout().flush();
+
+        match stdin.read_line(&mut buffer) {
+            Ok(0) => {
+                println!();
+                if let Err(err) = interp.parse(&buffer) {
+                    eprint!("{}", err);
+                }
+                return ExitCode::SUCCESS;
+            }
+            Ok(_) => (),
+            Err(err) => {
+                eprintln!("<stdin>: {}", err);
+                return ExitCode::FAILURE;
+            }
+        }
+
+        let code = match interp.parse(&buffer) {
+            Ok(code) => code,
+            Err(err) if err.is_incomplete() => continue,
+            Err(err) => {
+                eprint!("{}", err);
+                buffer.clear();
+                continue;
+            }
+        };
+        buffer.clear();
+
+        for form in code {
+            match interp.eval(vector![form]) {
+                Ok(Value::Unspecified) => (),
+                Ok(value) => println!("{:?}", value),
+                Err(err) => {
+                    report(&err);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match parse_args(env::args().skip(1)) {
+        Ok(Command::Run(args)) => run(args),
+        Ok(Command::Repl) => repl(),
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Err(msg) => {
+            eprintln!("lispizzle: {}\n\n{}", msg, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interpreter, parse_args, Args, Command, Source};
+    use lispizzle::Value;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn run(sources: Vec<Source>, argv: &[&str]) -> Result<Command, String> {
+        Ok(Command::Run(Args {
+            sources,
+            argv: argv.iter().map(|arg| arg.to_string()).collect(),
+        }))
+    }
+
+    #[test]
+    fn script() {
+        assert_eq!(
+            parse(&["main.zle", "-e", "x"]),
+            run(
+                vec![Source::File("main.zle".into())],
+                &["main.zle", "-e", "x"]
+            )
+        );
+        assert_eq!(
+            parse(&["-e", "(+ 1 2)", "-e", "x", "main.zle", "a"]),
+            run(
+                vec![
+                    Source::Expr("(+ 1 2)".to_string()),
+                    Source::Expr("x".to_string()),
+                    Source::File("main.zle".into()),
+                ],
+                &["main.zle", "a"]
+            )
+        );
+        assert_eq!(
+            parse(&["-e", "x"]),
+            run(vec![Source::Expr("x".to_string())], &[])
+        );
+    }
+
+    #[test]
+    fn stdin_and_separator() {
+        assert_eq!(
+            parse(&["-", "a", "-b"]),
+            run(vec![Source::Stdin], &["-", "a", "-b"])
+        );
+        assert_eq!(
+            parse(&["--", "-weird.zle", "a"]),
+            run(
+                vec![Source::File("-weird.zle".into())],
+                &["-weird.zle", "a"]
+            )
+        );
+        assert_eq!(
+            parse(&["-e", "x", "--"]),
+            run(vec![Source::Expr("x".to_string())], &[])
+        );
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(parse(&["repl"]), Ok(Command::Repl));
+        assert_eq!(parse(&["-h", "main.zle"]), Ok(Command::Help));
+        assert_eq!(parse(&["-e", "x", "--help"]), Ok(Command::Help));
+        assert_eq!(
+            parse(&["x.zle", "repl"]),
+            run(vec![Source::File("x.zle".into())], &["x.zle", "repl"])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse(&[]), Err("nothing to evaluate".to_string()));
+        assert_eq!(parse(&["--"]), Err("nothing to evaluate".to_string()));
+        assert_eq!(
+            parse(&["-e"]),
+            Err("option `-e' requires an argument".to_string())
+        );
+        assert_eq!(parse(&["-x"]), Err("unknown option `-x'".to_string()));
+        assert_eq!(
+            parse(&["repl", "x"]),
+            Err("too many arguments for `repl'".to_string())
+        );
+    }
+
+    #[test]
+    fn argv() {
+        let interp = interpreter(vec!["main.zle".to_string(), "a".to_string()]);
+        assert_eq!(
+            interp.eval_str("(if #t argv)").unwrap(),
+            Value::from(vec!["main.zle".into(), "a".into()])
+        );
+    }
 }
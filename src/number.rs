@@ -0,0 +1,464 @@
+use std::{cmp::Ordering, fmt};
+
+use im_rc::vector;
+use rug::{float, ops::Pow, Float, Integer, Rational};
+
+use crate::{Context, Error, FromValue, Value};
+
+/// A numeric value, used by the arithmetic builtins to promote their
+/// operands to a common type.
+///
+/// Integers that fit in 64 bits are kept inline and promoted to big integers
+/// when a result overflows. Integers are promoted to rationals, exact numbers
+/// are promoted to floats and everything is promoted to big floats.
+/// Operations producing big floats round their result to the precision they
+/// are given.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Number {
+    Fixnum(i64),
+    Integer(Integer),
+    Rational(Rational),
+    Float(f64),
+    BigFloat(Float),
+}
+
+impl Number {
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        match self {
+            &Self::Fixnum(i) => i == 0,
+            Self::Integer(i) => i.cmp0() == Ordering::Equal,
+            Self::Rational(r) => r.cmp0() == Ordering::Equal,
+            &Self::Float(x) => x == 0.0,
+            Self::BigFloat(f) => f.is_zero(),
+        }
+    }
+
+    #[inline]
+    pub fn is_nan(&self) -> bool {
+        match self {
+            Self::Float(x) => x.is_nan(),
+            Self::BigFloat(f) => f.is_nan(),
+            _ => false,
+        }
+    }
+
+    /// The ordering of the number with respect to zero, `None` for NaN.
+    pub fn sign(&self) -> Option<Ordering> {
+        match self {
+            Self::Fixnum(i) => Some(i.cmp(&0)),
+            Self::Integer(i) => Some(i.cmp0()),
+            Self::Rational(r) => Some(r.cmp0()),
+            Self::Float(x) => x.partial_cmp(&0.0),
+            Self::BigFloat(f) => f.cmp0(),
+        }
+    }
+
+    /// The value as an `i32` if it's an integer, `None` if it isn't and
+    /// `Some(None)` if it doesn't fit.
+    pub fn to_i32(&self) -> Option<Option<i32>> {
+        match self {
+            &Self::Fixnum(i) => Some(i.try_into().ok()),
+            Self::Integer(i) => Some(i.to_i32()),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn is_exact(&self) -> bool {
+        !matches!(self, Self::Float(_) | Self::BigFloat(_))
+    }
+
+    #[inline]
+    pub fn is_big(&self) -> bool {
+        matches!(self, Self::BigFloat(_))
+    }
+
+    fn into_integer(self) -> Integer {
+        match self {
+            Self::Fixnum(i) => Integer::from(i),
+            Self::Integer(i) => i,
+            _ => unreachable!("only integers are promoted to big integers"),
+        }
+    }
+
+    fn into_rational(self) -> Rational {
+        match self {
+            Self::Fixnum(i) => Rational::from(i),
+            Self::Integer(i) => Rational::from(i),
+            Self::Rational(r) => r,
+            Self::Float(_) | Self::BigFloat(_) => {
+                unreachable!("floats are never promoted to rationals")
+            }
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            &Self::Fixnum(i) => i as f64,
+            Self::Integer(i) => i.to_f64(),
+            Self::Rational(r) => r.to_f64(),
+            &Self::Float(x) => x,
+            Self::BigFloat(f) => f.to_f64(),
+        }
+    }
+
+    /// Converts to a big float rounded to `prec` bits, big floats are kept
+    /// as they are.
+    pub fn to_big(&self, prec: u32) -> Float {
+        match self {
+            &Self::Fixnum(i) => Float::with_val(prec, i),
+            Self::Integer(i) => Float::with_val(prec, i),
+            Self::Rational(r) => Float::with_val(prec, r),
+            &Self::Float(x) => Float::with_val(prec, x),
+            Self::BigFloat(f) => f.clone(),
+        }
+    }
+
+    /// Converts exact numbers to floats, or to big floats of `prec` bits if
+    /// `big` is set.
+    pub fn into_inexact(self, big: bool, prec: u32) -> Self {
+        match self {
+            inexact @ (Self::Float(_) | Self::BigFloat(_)) => inexact,
+            exact if big => Self::BigFloat(exact.to_big(prec)),
+            exact => Self::Float(exact.to_f64()),
+        }
+    }
+
+    /// The exact number with the same value, `None` for infinities and NaN.
+    pub fn to_exact(&self) -> Option<Self> {
+        match self {
+            &Self::Float(x) => Rational::from_f64(x).map(Self::Rational),
+            Self::BigFloat(f) => f.to_rational().map(Self::Rational),
+            exact => Some(exact.clone()),
+        }
+    }
+
+    /// Brings both operands to the same type and applies the matching
+    /// operation.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    fn promote<S, I, R, F, B>(
+        self,
+        other: Self,
+        prec: u32,
+        small: S,
+        int: I,
+        rat: R,
+        float: F,
+        big: B,
+    ) -> Self
+    where
+        S: FnOnce(i64, i64) -> Option<i64>,
+        I: FnOnce(Integer, Integer) -> Self,
+        R: FnOnce(Rational, Rational) -> Self,
+        F: FnOnce(f64, f64) -> f64,
+        B: FnOnce(&Float, &Float) -> Float,
+    {
+        match (self, other) {
+            (Self::Fixnum(a), Self::Fixnum(b)) => match small(a, b) {
+                Some(res) => Self::Fixnum(res),
+                None => int(a.into(), b.into()),
+            },
+            (
+                a @ (Self::Fixnum(_) | Self::Integer(_)),
+                b @ (Self::Fixnum(_) | Self::Integer(_)),
+            ) => int(a.into_integer(), b.into_integer()),
+            (a, b) if a.is_exact() && b.is_exact() => rat(a.into_rational(), b.into_rational()),
+            (a, b) if a.is_big() || b.is_big() => {
+                Self::BigFloat(big(&a.to_big(prec), &b.to_big(prec)))
+            }
+            (a, b) => Self::Float(float(a.to_f64(), b.to_f64())),
+        }
+    }
+
+    pub fn add(self, other: Self, prec: u32) -> Self {
+        self.promote(
+            other,
+            prec,
+            i64::checked_add,
+            |a, b| Self::Integer(a + b),
+            |a, b| Self::Rational(a + b),
+            |a, b| a + b,
+            |a, b| Float::with_val(prec, a + b),
+        )
+    }
+
+    pub fn sub(self, other: Self, prec: u32) -> Self {
+        self.promote(
+            other,
+            prec,
+            i64::checked_sub,
+            |a, b| Self::Integer(a - b),
+            |a, b| Self::Rational(a - b),
+            |a, b| a - b,
+            |a, b| Float::with_val(prec, a - b),
+        )
+    }
+
+    pub fn mul(self, other: Self, prec: u32) -> Self {
+        self.promote(
+            other,
+            prec,
+            i64::checked_mul,
+            |a, b| Self::Integer(a * b),
+            |a, b| Self::Rational(a * b),
+            |a, b| a * b,
+            |a, b| Float::with_val(prec, a * b),
+        )
+    }
+
+    /// Exact division if both operands are exact, floating point division
+    /// otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if both operands are exact and `other` is zero.
+    pub fn div(self, other: Self, prec: u32) -> Self {
+        self.promote(
+            other,
+            prec,
+            |a, b| match a.checked_rem(b) {
+                Some(0) => a.checked_div(b),
+                _ => None,
+            },
+            |a, b| Self::Rational(Rational::from((a, b))),
+            |a, b| Self::Rational(a / b),
+            |a, b| a / b,
+            |a, b| Float::with_val(prec, a / b),
+        )
+    }
+
+    /// Raises to an integer power, the result is exact if `self` is exact.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is an exact zero and `exponent` is negative.
+    pub fn pow(self, exponent: i32, prec: u32) -> Self {
+        match self {
+            Self::Fixnum(i) if exponent >= 0 => match i.checked_pow(exponent as u32) {
+                Some(res) => Self::Fixnum(res),
+                None => Self::Integer(Integer::from(i).pow(exponent as u32)),
+            },
+            Self::Integer(i) if exponent >= 0 => Self::Integer(i.pow(exponent as u32)),
+            Self::Float(x) => Self::Float(x.powi(exponent)),
+            Self::BigFloat(f) => Self::BigFloat(Float::with_val(prec, (&f).pow(exponent))),
+            exact => Self::Rational(exact.into_rational().pow(exponent)),
+        }
+    }
+
+    /// An upper bound on the number of bits of the numerator and denominator
+    /// of `self.pow(exponent)`, for exact numbers.
+    pub fn pow_bits(&self, exponent: i32) -> u64 {
+        let bits = match self {
+            Self::Fixnum(i) => u64::BITS - i.unsigned_abs().leading_zeros(),
+            Self::Integer(i) => i.significant_bits(),
+            Self::Rational(r) => r
+                .numer()
+                .significant_bits()
+                .max(r.denom().significant_bits()),
+            Self::Float(_) | Self::BigFloat(_) => 0,
+        };
+        u64::from(bits.saturating_sub(1)) * u64::from(exponent.unsigned_abs()) + 1
+    }
+
+    /// Raises to an arbitrary power, the result is always inexact.
+    pub fn powf(self, exponent: Self, prec: u32) -> Self {
+        if self.is_big() || exponent.is_big() {
+            Self::BigFloat(Float::with_val(
+                prec,
+                (&self.to_big(prec)).pow(&exponent.to_big(prec)),
+            ))
+        } else {
+            Self::Float(self.to_f64().powf(exponent.to_f64()))
+        }
+    }
+
+    /// Rounds to an integral value, exact numbers are rounded to integers
+    /// and floats to integral floats.
+    fn round_with(
+        self,
+        rat: fn(Rational) -> Rational,
+        float: fn(f64) -> f64,
+        big: fn(Float) -> Float,
+    ) -> Self {
+        match self {
+            integer @ (Self::Fixnum(_) | Self::Integer(_)) => integer,
+            Self::Rational(r) => Self::Integer(rat(r).into_numer_denom().0),
+            Self::Float(x) => Self::Float(float(x)),
+            Self::BigFloat(f) => Self::BigFloat(big(f)),
+        }
+    }
+
+    #[inline]
+    pub fn floor(self) -> Self {
+        self.round_with(Rational::floor, f64::floor, Float::floor)
+    }
+
+    #[inline]
+    pub fn ceil(self) -> Self {
+        self.round_with(Rational::ceil, f64::ceil, Float::ceil)
+    }
+
+    #[inline]
+    pub fn trunc(self) -> Self {
+        self.round_with(Rational::trunc, f64::trunc, Float::trunc)
+    }
+
+    /// Rounds to the nearest integral value, ties to the even one.
+    #[inline]
+    pub fn round(self) -> Self {
+        self.round_with(
+            |r| {
+                let floor = r.clone().floor();
+                match (r - &floor).cmp(&Rational::from((1, 2))) {
+                    Ordering::Less => floor,
+                    Ordering::Greater => floor + 1,
+                    Ordering::Equal if floor.numer().is_even() => floor,
+                    Ordering::Equal => floor + 1,
+                }
+            },
+            f64::round_ties_even,
+            Float::round_even,
+        )
+    }
+
+    #[inline]
+    pub fn abs(self) -> Self {
+        match self {
+            Self::Fixnum(i) => match i.checked_abs() {
+                Some(res) => Self::Fixnum(res),
+                None => Self::Integer(Integer::from(i).abs()),
+            },
+            Self::Integer(i) => Self::Integer(i.abs()),
+            Self::Rational(r) => Self::Rational(r.abs()),
+            Self::Float(x) => Self::Float(x.abs()),
+            Self::BigFloat(f) => Self::BigFloat(f.abs()),
+        }
+    }
+
+    #[inline]
+    pub fn neg(self) -> Self {
+        match self {
+            Self::Fixnum(i) => match i.checked_neg() {
+                Some(res) => Self::Fixnum(res),
+                None => Self::Integer(-Integer::from(i)),
+            },
+            Self::Integer(i) => Self::Integer(-i),
+            Self::Rational(r) => Self::Rational(-r),
+            Self::Float(x) => Self::Float(-x),
+            Self::BigFloat(f) => Self::BigFloat(-f),
+        }
+    }
+
+    /// Compares exactly, floats are never rounded to compare them with exact
+    /// numbers. `None` if one of the operands is NaN.
+    pub fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Fixnum(a), Self::Fixnum(b)) => Some(a.cmp(b)),
+            (&Self::Fixnum(a), b) => Self::Integer(a.into()).partial_cmp(b),
+            (a, &Self::Fixnum(b)) => a.partial_cmp(&Self::Integer(b.into())),
+            (Self::Integer(a), Self::Integer(b)) => Some(a.cmp(b)),
+            (Self::Rational(a), Self::Rational(b)) => Some(a.cmp(b)),
+            (Self::Rational(a), Self::Integer(b)) => a.partial_cmp(b),
+            (Self::Integer(a), Self::Rational(b)) => b.partial_cmp(a).map(Ordering::reverse),
+            (Self::Float(a), Self::Float(b)) => a.partial_cmp(b),
+            (Self::BigFloat(a), Self::BigFloat(b)) => a.partial_cmp(b),
+            (Self::BigFloat(a), Self::Integer(b)) => a.partial_cmp(b),
+            (Self::BigFloat(a), Self::Rational(b)) => a.partial_cmp(b),
+            (Self::BigFloat(a), Self::Float(b)) => a.partial_cmp(b),
+            (a, Self::BigFloat(_)) => other.partial_cmp(a).map(Ordering::reverse),
+            (&Self::Float(a), b) => Self::cmp_float(a, b),
+            (a, &Self::Float(b)) => Self::cmp_float(b, a).map(Ordering::reverse),
+        }
+    }
+
+    fn cmp_float(a: f64, b: &Self) -> Option<Ordering> {
+        if a.is_nan() {
+            None
+        } else if a.is_infinite() {
+            Some(if a > 0.0 {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            })
+        } else {
+            Self::Rational(Rational::from_f64(a)?).partial_cmp(b)
+        }
+    }
+}
+
+impl FromValue for Number {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Integer(i) => Ok(match i.to_i64() {
+                Some(i) => Self::Fixnum(i),
+                None => Self::Integer(i),
+            }),
+            Value::Rational(r) => Ok(Self::Rational(r)),
+            Value::Float(x) => Ok(Self::Float(x)),
+            Value::BigFloat(f) => Ok(Self::BigFloat(f)),
+            _ => Err(value),
+        }
+    }
+}
+
+impl From<Number> for Value {
+    #[inline]
+    fn from(value: Number) -> Self {
+        match value {
+            Number::Fixnum(i) => i.into(),
+            Number::Integer(i) => i.into(),
+            Number::Rational(r) => r.into(),
+            Number::Float(x) => x.into(),
+            Number::BigFloat(f) => f.into(),
+        }
+    }
+}
+
+/// Checks that `p` is a valid big float precision.
+pub(crate) fn precision(ctx: &Context, p: Integer) -> Result<u32, Error> {
+    match p.to_u32() {
+        Some(p) if (float::prec_min()..=float::prec_max()).contains(&p) => Ok(p),
+        _ => Err(ctx.trace().error("out-of-range", Some(vector![p.into()]))),
+    }
+}
+
+/// Writes a float so that it reads back as a float: integral values get a
+/// trailing `.0` and infinities and NaN are written as `+inf.0`, `-inf.0`
+/// and `+nan.0`.
+pub(crate) fn fmt_float(x: f64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if x.is_nan() {
+        f.write_str("+nan.0")
+    } else if x.is_infinite() {
+        f.write_str(if x > 0.0 { "+inf.0" } else { "-inf.0" })
+    } else {
+        write!(f, "{:?}", x)
+    }
+}
+
+/// Writes a big float with an `L` exponent marker so that it reads back as a
+/// big float, e.g. `1.5L0` or `-2.25L-10`. Infinities and NaN are written
+/// like the ones of [`fmt_float`].
+pub(crate) fn fmt_bigfloat(x: &Float, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if !x.is_finite() {
+        return fmt_float(x.to_f64(), f);
+    }
+
+    let s = x.to_string();
+    let (mantissa, exponent) = s.split_once('e').unwrap_or((&s, "0"));
+    let mantissa = match mantissa.split_once('.') {
+        Some((int, frac)) => {
+            let frac = frac.trim_end_matches('0');
+            if frac.is_empty() {
+                format!("{}.0", int)
+            } else {
+                format!("{}.{}", int, frac)
+            }
+        }
+        None => format!("{}.0", mantissa),
+    };
+
+    write!(f, "{}L{}", mantissa, exponent)
+}
@@ -84,6 +84,16 @@ pub struct Error {
     pub(crate) message: Message,
     pub(crate) location: Location,
     pub(crate) line: Box<str>,
+    pub(crate) incomplete: bool,
+}
+
+impl Error {
+    /// Returns `true` if the error has been caused by the input ending in the
+    /// middle of a form, so that more input could make it valid.
+    #[inline]
+    pub fn is_incomplete(&self) -> bool {
+        self.incomplete
+    }
 }
 
 impl fmt::Display for Error {
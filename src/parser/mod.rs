@@ -16,7 +16,7 @@ use std::{
 pub use error::*;
 use im_rc::Vector;
 pub use location::Location;
-pub use span::Span;
+pub use span::{SourceSpan, Span};
 use thiserror::Error;
 
 use crate::{str_cache::StrCache, Value};
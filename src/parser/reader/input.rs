@@ -2,6 +2,7 @@ use std::{
     borrow::Borrow,
     ops::{Deref, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive},
     path::Path,
+    rc::Rc,
 };
 
 use ecow::EcoVec;
@@ -17,6 +18,7 @@ use crate::StrCache;
 #[derive(Debug, Clone)]
 pub struct Input<'a> {
     path: Option<&'a Path>,
+    origin: Option<Rc<str>>,
     inner: StringReader<'a>,
     need_ws: bool,
     str_cache: StrCache,
@@ -32,12 +34,20 @@ impl<'a> Input<'a> {
     pub fn with_cache(path: Option<&'a Path>, text: &'a str, str_cache: StrCache) -> Self {
         Self {
             path,
+            origin: path.map(|p| Rc::from(p.to_string_lossy())),
             inner: StringReader::new(text),
             need_ws: false,
             str_cache,
         }
     }
 
+    /// The path of the file being read, shared between all the spans read
+    /// from it.
+    #[inline]
+    pub fn origin(&self) -> Option<Rc<str>> {
+        self.origin.clone()
+    }
+
     #[inline]
     pub fn needs_ws(&self) -> bool {
         self.need_ws
@@ -69,6 +79,7 @@ impl<'a> Input<'a> {
     pub fn ltrim(self) -> Self {
         Self {
             path: self.path,
+            origin: self.origin.clone(),
             inner: self.inner.ltrim(),
             need_ws: self.need_ws,
             str_cache: self.str_cache,
@@ -82,12 +93,14 @@ impl<'a> Input<'a> {
         Some((
             Self {
                 path: self.path,
+                origin: self.origin.clone(),
                 inner: a,
                 need_ws: self.need_ws,
                 str_cache: self.str_cache.clone(),
             },
             Self {
                 path: self.path,
+                origin: self.origin,
                 inner: b,
                 need_ws: self.need_ws,
                 str_cache: self.str_cache,
@@ -99,6 +112,7 @@ impl<'a> Input<'a> {
     pub fn skip_until_nl(self) -> Self {
         Self {
             path: self.path,
+            origin: self.origin.clone(),
             inner: self.inner.skip_until_nl(),
             need_ws: self.need_ws,
             str_cache: self.str_cache,
@@ -110,15 +124,25 @@ impl<'a> Input<'a> {
         self.str_cache.get(s)
     }
 
+    #[inline]
     pub fn err<M: Into<Message>>(self, message: M) -> Error {
+        self.error(message, false)
+    }
+
+    #[inline]
+    pub fn eof<M: Into<Message>>(self, message: M) -> Error {
+        self.error(message, true)
+    }
+
+    fn error<M: Into<Message>>(self, message: M, incomplete: bool) -> Error {
         Error {
             path: self
                 .path
-                .and_then(|p| p.to_str())
-                .map(|s| s.to_string().into_boxed_str()),
+                .map(|p| p.to_string_lossy().into_owned().into_boxed_str()),
             message: message.into(),
             location: self.location(),
             line: self.line_str().to_string().into_boxed_str(),
+            incomplete,
         }
     }
 
@@ -180,6 +204,7 @@ impl Slice<RangeFrom<usize>> for Input<'_> {
     fn get(&self, index: RangeFrom<usize>) -> Option<Self::Output> {
         Some(Self {
             path: self.path,
+            origin: self.origin.clone(),
             inner: self.inner.get(index)?,
             need_ws: self.need_ws,
             str_cache: self.str_cache.clone(),
@@ -194,6 +219,7 @@ impl Slice<RangeInclusive<usize>> for Input<'_> {
     fn get(&self, index: RangeInclusive<usize>) -> Option<Self::Output> {
         Some(Self {
             path: self.path,
+            origin: self.origin.clone(),
             inner: self.inner.get(index)?,
             need_ws: self.need_ws,
             str_cache: self.str_cache.clone(),
@@ -208,6 +234,7 @@ impl Slice<Range<usize>> for Input<'_> {
     fn get(&self, index: Range<usize>) -> Option<Self::Output> {
         Some(Self {
             path: self.path,
+            origin: self.origin.clone(),
             inner: self.inner.get(index)?,
             need_ws: self.need_ws,
             str_cache: self.str_cache.clone(),
@@ -222,6 +249,7 @@ impl Slice<RangeTo<usize>> for Input<'_> {
     fn get(&self, index: RangeTo<usize>) -> Option<Self::Output> {
         Some(Self {
             path: self.path,
+            origin: self.origin.clone(),
             inner: self.inner.get(index)?,
             need_ws: self.need_ws,
             str_cache: self.str_cache.clone(),
@@ -236,6 +264,7 @@ impl Slice<RangeToInclusive<usize>> for Input<'_> {
     fn get(&self, index: RangeToInclusive<usize>) -> Option<Self::Output> {
         Some(Self {
             path: self.path,
+            origin: self.origin.clone(),
             inner: self.inner.get(index)?,
             need_ws: self.need_ws,
             str_cache: self.str_cache.clone(),
@@ -250,6 +279,7 @@ impl Slice<RangeFull> for Input<'_> {
     fn get(&self, index: RangeFull) -> Option<Self::Output> {
         Some(Self {
             path: self.path,
+            origin: self.origin.clone(),
             inner: self.inner.get(index)?,
             need_ws: self.need_ws,
             str_cache: self.str_cache.clone(),
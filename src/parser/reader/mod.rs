@@ -5,17 +5,19 @@ pub mod util;
 #[cfg(not(bench))]
 pub(crate) mod util;
 
+use std::rc::Rc;
+
 use ecow::EcoVec;
 use im_rc::{vector, Vector};
 use phf::phf_map;
 
-use rug::{Complete, Integer};
+use rug::{Complete, Float, Integer, Rational};
 
-use crate::{Str, Symbol, Value};
+use crate::{Context, List, Str, Symbol, Value};
 
 pub use input::Input;
 
-use super::Error;
+use super::{Error, SourceSpan, Span};
 
 use str_reader::*;
 
@@ -31,6 +33,11 @@ use str_reader::*;
 //               #o[+-]?[0-7]+
 //               #x[+-]?[0-9a-fA-F]+
 //               #b[+-]?[0-1]+
+// ✔️  Rational   [+-]?[0-9]+/[0-9]+
+// ✔️  Float      [+-]?([0-9]+\.[0-9]*|\.?[0-9]+)([eE][+-]?[0-9]+)?
+//               [+-]inf\.0
+//               [+-]nan\.0
+// ✔️  BigFloat   [+-]?([0-9]+\.[0-9]*|\.?[0-9]+)[lL][+-]?[0-9]+
 // ✔️  Symbol     [^\s,'@`()\"|#]+
 // ✔️  List       ((list|literal)*)
 
@@ -175,7 +182,7 @@ fn needs_char(i: Input, need: char) -> std::result::Result<Input, Error> {
             Err(i.err("unexpected character"))
         }
     } else {
-        Err(i.err("unexpected EOF"))
+        Err(i.eof("unexpected EOF"))
     }
 }
 
@@ -231,6 +238,65 @@ fn hex_number<'a>(init: Input, i: Input<'a>) -> Result<'a, Value> {
     )
 }
 
+/// Whether `s` looks like `[+-]?[0-9]+/[0-9]+`.
+fn is_rational(s: &str) -> bool {
+    let s = s.strip_prefix(&['+', '-'][..]).unwrap_or(s);
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    s.split_once('/')
+        .map(|(n, d)| is_digits(n) && is_digits(d))
+        .unwrap_or(false)
+}
+
+/// Parses `s` if it's a decimal with a fractional part or an exponent, or
+/// one of the special values `+inf.0`, `-inf.0` and `+nan.0`.
+///
+/// An `L` exponent marker makes a big float, precise enough to hold all the
+/// written digits and never less precise than
+/// [`Context::DEFAULT_PRECISION`].
+fn parse_float(s: &str) -> Option<Value> {
+    match s {
+        "+inf.0" => return Some(f64::INFINITY.into()),
+        "-inf.0" => return Some(f64::NEG_INFINITY.into()),
+        "+nan.0" | "-nan.0" => return Some(f64::NAN.into()),
+        _ => (),
+    }
+
+    fn unsigned(s: &str) -> &str {
+        s.strip_prefix(&['+', '-'][..]).unwrap_or(s)
+    }
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+
+    let (mantissa, exponent) = match unsigned(s).split_once(&['e', 'E', 'l', 'L'][..]) {
+        Some((m, e)) => (m, Some(unsigned(e))),
+        None => (unsigned(s), None),
+    };
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None),
+    };
+
+    let valid_mantissa = is_digits(int)
+        && is_digits(frac.unwrap_or_default())
+        && !(int.is_empty() && frac.unwrap_or_default().is_empty());
+    let valid_exponent = match exponent {
+        Some(e) => !e.is_empty() && is_digits(e),
+        None => true,
+    };
+
+    if !(valid_mantissa && valid_exponent && (frac.is_some() || exponent.is_some())) {
+        None
+    } else if s.contains(&['l', 'L'][..]) {
+        let digits = int.len() + frac.unwrap_or_default().len();
+        let precision = ((digits as f64 * std::f64::consts::LOG2_10).ceil() as u32)
+            .max(Context::DEFAULT_PRECISION);
+        let parsed = Float::parse(s.replacen(&['l', 'L'][..], "e", 1)).ok()?;
+        Some(Float::with_val(precision, parsed).into())
+    } else {
+        s.parse::<f64>().ok().map(Value::from)
+    }
+}
+
 fn symbol_or_integer(i: Input) -> Result<Value> {
     let (len, is_integer) = {
         let mut is_integer = false;
@@ -243,7 +309,7 @@ fn symbol_or_integer(i: Input) -> Result<Value> {
             .take_while(|&c| is_valid_sym_char(c))
             .enumerate()
         {
-            if is_integer || sign || i == 0 {
+            if is_integer || (sign && i == 1) || i == 0 {
                 is_integer = c.is_ascii_digit();
             }
 
@@ -268,12 +334,58 @@ fn symbol_or_integer(i: Input) -> Result<Value> {
             .map_err(|_| parsed.err("invalid number"))?
             .complete()
             .into())
+    } else if is_rational(parsed.as_str()) {
+        let (n, d) = unsafe { parsed.as_str().split_once('/').unwrap_unchecked() };
+        let n = Integer::parse(n).map_err(|_| parsed.clone().err("invalid number"))?;
+        let d = Integer::parse(d).map_err(|_| parsed.clone().err("invalid number"))?;
+        let d = d.complete();
+
+        if d == 0 {
+            return Err(parsed.err("division by zero"));
+        }
+        i.ok(Rational::from((n.complete(), d)).into())
+    } else if let Some(x) = parse_float(parsed.as_str()) {
+        i.ok(x)
     } else {
         let name = i.make_string(parsed);
         i.ok(Value::Symbol(Symbol::Name(name)))
     }
 }
 
+/// Parses a whole string as a number literal, the way the reader does.
+///
+/// Digits without a `#b`, `#o` or `#x` prefix are read in `radix`, which
+/// must be between 2 and 36. Returns `None` if `s` isn't a number.
+pub(crate) fn parse_number(s: &str, radix: u32) -> Option<Value> {
+    let res = if s.starts_with('#') {
+        hash_prefixed(Input::new(None, s))
+    } else if radix == 10 {
+        symbol_or_integer(Input::new(None, s))
+    } else {
+        let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_digit(radix));
+        let parse = |s: &str| Integer::parse_radix(s, radix as i32).map(Complete::complete);
+        let unsigned = s.strip_prefix(&['+', '-'][..]).unwrap_or(s);
+
+        return match unsigned.split_once('/') {
+            None if is_digits(unsigned) => parse(s).ok().map(Value::from),
+            Some((n, d)) if is_digits(n) && is_digits(d) => {
+                let (n, d) = s.split_once('/')?;
+                let d = parse(d).ok().filter(|d| *d != 0)?;
+                Some(Rational::from((parse(n).ok()?, d)).into())
+            }
+            _ => None,
+        };
+    };
+
+    match res {
+        Ok((
+            rest,
+            value @ (Value::Integer(_) | Value::Rational(_) | Value::Float(_) | Value::BigFloat(_)),
+        )) if rest.is_empty() => Some(value),
+        _ => None,
+    }
+}
+
 fn parse_nibble(i: Input) -> Option<(Input, u8)> {
     next_char(i).and_then(|(c, i)| c.to_digit(16).map(|c| (i, c as u8)))
 }
@@ -384,7 +496,7 @@ fn string(i: Input) -> Result<Value> {
                 }
             }
         } else {
-            return Err(i.err("unexpected EOF"));
+            return Err(i.eof("unexpected EOF"));
         }
         prev_input = pinput;
     }
@@ -394,19 +506,17 @@ fn parse_char<'a>(init: Input<'a>, i: Input<'a>) -> Result<'a, Value> {
     if let Some((c, i)) = next_char(i.clone()) {
         if c == ' ' {
             return i.unset_needs_ws().ok(' '.into());
-        } else if c == '(' && c == ')' {
-            return i.set_needs_ws().ok(' '.into());
+        } else if c == '(' || c == ')' {
+            return i.set_needs_ws().ok(c.into());
         }
     }
 
-    let (i, rest) = if let Some(x) = i
-        .clone()
-        .split_at(|c| c.is_whitespace() || c == '(' || c == ')')
-    {
-        x
-    } else {
-        (i.clone(), unsafe { i.get(i.len()..).unwrap_unchecked() })
-    };
+    let len = i
+        .as_str()
+        .chars()
+        .take_while(|&c| !(c.is_whitespace() || c == '(' || c == ')'))
+        .count();
+    let (i, rest) = unsafe { split_at(i, len).unwrap_unchecked() };
     let rest = rest.set_needs_ws();
 
     if i.is_empty() {
@@ -462,7 +572,7 @@ fn hash_prefixed(i: Input) -> Result<Value> {
     } else if let Some(i) = istarts_with_ci(i.clone(), "x") {
         hex_number(init, i)
     } else if i.is_empty() {
-        Err(i.err("unexpected EOF"))
+        Err(i.eof("unexpected EOF"))
     } else {
         Err(i.err("unexpected character"))
     }
@@ -481,10 +591,11 @@ fn literal(i: Input) -> Result<Value> {
 }
 
 fn list(mut i: Input) -> Result<Value> {
+    let start = i.location();
     i = if let Some(i) = istarts_with(i.clone(), "(") {
         i.unset_needs_ws()
     } else if i.is_empty() {
-        return Err(i.err("unexpected EOF"));
+        return Err(i.eof("unexpected EOF"));
     } else {
         return Err(i.err("expected `('"));
     };
@@ -495,14 +606,22 @@ fn list(mut i: Input) -> Result<Value> {
         i = skip_ws(i)?;
         if let Some((c, new_i)) = next_char(i.clone()) {
             if c == ')' {
-                return Ok((new_i.unset_needs_ws(), Value::List(values)));
+                let span = SourceSpan {
+                    path: i.origin(),
+                    span: Span {
+                        start,
+                        stop: i.location(),
+                    },
+                };
+                let list = List::with_span(values, Rc::new(span));
+                return Ok((new_i.unset_needs_ws(), Value::List(list)));
             } else {
                 let v;
                 (i, v) = expression(i)?;
                 values.push_back(v);
             }
         } else {
-            return Err(i.err("expected `)'"));
+            return Err(i.eof("expected `)'"));
         }
     }
 }
@@ -534,7 +653,7 @@ fn expression(i: Input) -> Result<Value> {
             literal(i)
         }
     } else {
-        Err(i.err("unexpected EOF"))
+        Err(i.eof("unexpected EOF"))
     }
 }
 
@@ -562,9 +681,12 @@ pub fn parse(mut i: Input) -> std::result::Result<Vector<Value>, Error> {
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+
     use im_rc::vector;
 
     use super::*;
+    use crate::parser::Location;
 
     macro_rules! assert_fp_eq {
         ($e1:expr, $e2:expr) => {{
@@ -606,12 +728,95 @@ mod tests {
         assert_fp_eq!(expression(Input::new(None, "#x-ff")), (-255).into());
     }
 
+    #[test]
+    fn rational() {
+        assert_fp_eq!(
+            symbol_or_integer(Input::new(None, "1/3")),
+            Rational::from((1, 3)).into()
+        );
+        assert_fp_eq!(
+            symbol_or_integer(Input::new(None, "-2/6")),
+            Rational::from((-1, 3)).into()
+        );
+        assert_fp_eq!(symbol_or_integer(Input::new(None, "+4/2")), 2.into());
+        assert_fp_eq!(
+            symbol_or_integer(Input::new(None, "1/")),
+            Value::Symbol(Symbol::Name("1/".into()))
+        );
+        assert!(symbol_or_integer(Input::new(None, "1/0")).is_err());
+    }
+
+    #[test]
+    fn float() {
+        assert_fp_eq!(symbol_or_integer(Input::new(None, "1.5")), 1.5.into());
+        assert_fp_eq!(symbol_or_integer(Input::new(None, "-.5")), (-0.5).into());
+        assert_fp_eq!(symbol_or_integer(Input::new(None, "1.")), 1.0.into());
+        assert_fp_eq!(symbol_or_integer(Input::new(None, "1e10")), 1e10.into());
+        assert_fp_eq!(
+            symbol_or_integer(Input::new(None, "+2.5E-3")),
+            2.5e-3.into()
+        );
+        assert_fp_eq!(
+            symbol_or_integer(Input::new(None, "+inf.0")),
+            f64::INFINITY.into()
+        );
+        assert_fp_eq!(
+            symbol_or_integer(Input::new(None, "-inf.0")),
+            f64::NEG_INFINITY.into()
+        );
+        assert_fp_eq!(
+            symbol_or_integer(Input::new(None, "+nan.0")),
+            f64::NAN.into()
+        );
+
+        let big = |s: &str| match symbol_or_integer(Input::new(None, s)) {
+            Ok((_, Value::BigFloat(f))) => f,
+            res => panic!("{} is not a big float: {:?}", s, res),
+        };
+        assert_eq!(big("1.5L0"), 1.5);
+        assert_eq!(big("-25l-1"), -2.5);
+        assert_eq!(big("1.5L0").prec(), Context::DEFAULT_PRECISION);
+        let pi = big("3.14159265358979323846264338327950288419716939937510582097494459L0");
+        assert!(pi.prec() > Context::DEFAULT_PRECISION);
+        assert!(pi != std::f64::consts::PI);
+
+        for sym in [
+            ".", "...", "1e", "1.2.3", "e10", "-.e1", "inf", "1L", "1.5e3L2",
+        ] {
+            assert_fp_eq!(
+                symbol_or_integer(Input::new(None, sym)),
+                Value::Symbol(Symbol::Name(sym.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn number() {
+        assert_eq!(parse_number("42", 10), Some(42.into()));
+        assert_eq!(parse_number("-1.5e2", 10), Some((-150.0).into()));
+        assert_eq!(parse_number("#xff", 10), Some(255.into()));
+        assert_eq!(parse_number("#b-101", 16), Some((-5).into()));
+        assert_eq!(parse_number("ff", 16), Some(255.into()));
+        assert_eq!(parse_number("-z", 36), Some((-35).into()));
+        assert_eq!(parse_number("a/c", 16), Some(Rational::from((5, 6)).into()));
+        assert_eq!(parse_number("1/0", 16), None);
+        assert_eq!(parse_number("12", 2), None);
+        assert_eq!(parse_number("abc", 10), None);
+        assert_eq!(parse_number("#t", 10), None);
+        assert_eq!(parse_number("1 2", 10), None);
+        assert_eq!(parse_number("", 10), None);
+    }
+
     #[test]
     fn symbol() {
         assert_fp_eq!(
             symbol_or_integer(Input::new(None, "-0000000test")),
             Value::Symbol(Symbol::Name("-0000000test".into()))
         );
+        assert_fp_eq!(
+            symbol_or_integer(Input::new(None, "-a1")),
+            Value::Symbol(Symbol::Name("-a1".into()))
+        );
     }
 
     #[test]
@@ -622,6 +827,38 @@ mod tests {
         assert_fp_eq!(list(Input::new(None, "(1    (2    3)  )")), expected);
     }
 
+    #[test]
+    fn spans() {
+        let code = parse(Input::new(Some(Path::new("test.zle")), "(1\n  (2 3))")).unwrap();
+
+        let outer = code[0].span().unwrap();
+        assert_eq!(format!("{}", outer), "test.zle:1:1");
+        assert_eq!(outer.span.stop, Location { line: 2, column: 8 });
+
+        let inner = match code[0] {
+            Value::List(ref l) => l[1].span().unwrap(),
+            _ => unreachable!(),
+        };
+        assert_eq!(format!("{}", inner), "test.zle:2:3");
+        assert_eq!(inner.span.stop, Location { line: 2, column: 7 });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = Path::new(OsStr::from_bytes(b"caf\xe9.zle"));
+        let code = parse(Input::new(Some(path), "(1)")).unwrap();
+        assert_eq!(
+            format!("{}", code[0].span().unwrap()),
+            "caf\u{fffd}.zle:1:1"
+        );
+
+        let err = parse(Input::new(Some(path), "(1")).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("caf\u{fffd}.zle"));
+    }
+
     #[test]
     fn parse_string() {
         assert_fp_eq!(string(Input::new(None, "\"ciao\"")), "ciao".into());
@@ -649,5 +886,31 @@ mod tests {
         assert_fp_eq!(hash_prefixed(Input::new(None, "#\\12")), '\n'.into());
         assert_fp_eq!(hash_prefixed(Input::new(None, "#\\n")), 'n'.into());
         assert_fp_eq!(hash_prefixed(Input::new(None, "#\\ ")), ' '.into());
+        assert_fp_eq!(hash_prefixed(Input::new(None, "#\\(")), '('.into());
+        assert_fp_eq!(
+            list(Input::new(None, "(#\\a #\\space #\\))")),
+            vector!['a'.into(), ' '.into(), ')'.into()].into()
+        );
+    }
+
+    #[test]
+    fn incomplete() {
+        assert!(parse(Input::new(None, "(+ 1")).unwrap_err().is_incomplete());
+        assert!(parse(Input::new(None, "(+ 1 (- 2"))
+            .unwrap_err()
+            .is_incomplete());
+        assert!(parse(Input::new(None, "(print \"ciao"))
+            .unwrap_err()
+            .is_incomplete());
+        assert!(parse(Input::new(None, "(quote '"))
+            .unwrap_err()
+            .is_incomplete());
+        assert!(parse(Input::new(None, "(list #"))
+            .unwrap_err()
+            .is_incomplete());
+        assert!(!parse(Input::new(None, "(+ 1))"))
+            .unwrap_err()
+            .is_incomplete());
+        assert!(!parse(Input::new(None, "(#z)")).unwrap_err().is_incomplete());
     }
 }
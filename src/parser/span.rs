@@ -1,7 +1,41 @@
+use std::{fmt, rc::Rc};
+
 use super::Location;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub struct Span {
     pub start: Location,
     pub stop: Location,
 }
+
+/// The place a form has been read from: the file path, if any, and the
+/// span of the form in it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SourceSpan {
+    pub path: Option<Rc<str>>,
+    pub span: Span,
+}
+
+impl SourceSpan {
+    #[inline]
+    pub fn path(&self) -> &str {
+        self.path.as_deref().unwrap_or("<unknown>")
+    }
+
+    #[inline]
+    pub fn start(&self) -> Location {
+        self.span.start
+    }
+}
+
+impl fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.path(),
+            self.span.start.line,
+            self.span.start.column
+        )
+    }
+}
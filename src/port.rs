@@ -0,0 +1,43 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use crate::{Foreign, Value};
+
+/// A destination for the text written by lisp code.
+///
+/// Lisp code gets ports as foreign values, from `current-output-port` or
+/// from the embedding program, which converts them with `Value::from`.
+pub struct OutputPort(RefCell<Box<dyn Write>>);
+
+impl OutputPort {
+    #[inline]
+    pub fn new<W: Write + 'static>(writer: W) -> Self {
+        Self(RefCell::new(Box::new(writer)))
+    }
+
+    /// A port writing to the standard output of the process.
+    #[inline]
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    #[inline]
+    pub fn write_str(&self, s: &str) -> io::Result<()> {
+        self.0.borrow_mut().write_all(s.as_bytes())
+    }
+
+    #[inline]
+    pub fn flush(&self) -> io::Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+impl From<OutputPort> for Value {
+    #[inline]
+    fn from(value: OutputPort) -> Self {
+        Foreign::with_type_name(Rc::new(value), "output-port").into()
+    }
+}
@@ -1,7 +1,9 @@
 mod lisp;
 mod native;
+mod typed;
 
 pub use lisp::UnboundProc;
+pub use typed::{NativeFn, WithContext};
 
 use std::{fmt, num::NonZeroUsize};
 
@@ -103,6 +105,12 @@ impl Proc {
         }
     }
 
+    /// Wraps a typed Rust function, see [`NativeFn`].
+    #[inline]
+    pub fn from_fn<Args, F: NativeFn<Args>>(doc: Option<Str>, fun: F) -> Self {
+        fun.into_proc(doc)
+    }
+
     #[inline]
     pub fn name(&self) -> Option<Symbol> {
         self.name.clone()
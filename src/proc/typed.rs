@@ -0,0 +1,185 @@
+use std::num::NonZeroUsize;
+
+use im_rc::{vector, Vector};
+
+use super::{Parameters, Proc};
+use crate::{Context, Error, FromValue, IntoValue, Str, Value};
+
+/// Marker for native functions taking the [`Context`] as first argument.
+pub struct WithContext;
+
+/// A Rust function that can be turned into a native [`Proc`].
+///
+/// Implemented for closures taking up to 8 arguments that implement
+/// [`FromValue`] and returning a `Result` of something implementing
+/// [`IntoValue`], optionally preceded by a [`Context`]. Arguments are
+/// converted before the call and a `wrong-type-arg` error holding the
+/// offending value is raised when a conversion fails. Trailing `Option`
+/// arguments can be left out of the call.
+pub trait NativeFn<Args>: 'static {
+    fn into_proc(self, doc: Option<Str>) -> Proc;
+}
+
+fn parameters(optional: &[bool]) -> Parameters<usize, NonZeroUsize> {
+    let max = optional.len();
+    let min = max - optional.iter().rev().take_while(|&&o| o).count();
+
+    if min == max {
+        Parameters::Exact(max)
+    } else {
+        Parameters::Variadic(unsafe { NonZeroUsize::new_unchecked(min + 1) })
+    }
+}
+
+#[inline]
+fn arg<T: FromValue>(ctx: &Context, value: Option<Value>) -> Result<T, Error> {
+    T::from_value(value.unwrap_or(Value::Nil))
+        .map_err(|value| ctx.trace().error("wrong-type-arg", Some(vector![value])))
+}
+
+#[inline]
+fn check_arity(ctx: &Context, values: &Vector<Value>, max: usize) -> Result<(), Error> {
+    if values.len() > max {
+        Err(ctx.trace().error("wrong-number-of-args", None))
+    } else {
+        Ok(())
+    }
+}
+
+macro_rules! impl_native_fn {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg,)*> NativeFn<($($arg,)*)> for F
+        where
+            F: (Fn($($arg),*) -> Result<R, Error>) + 'static,
+            R: IntoValue,
+            $($arg: FromValue,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_proc(self, doc: Option<Str>) -> Proc {
+                let optional = [$($arg::OPTIONAL),*];
+                let max = optional.len();
+
+                Proc::from_native(parameters(&optional), doc, move |ctx, values| {
+                    check_arity(&ctx, &values, max)?;
+                    let mut values = values.into_iter();
+                    $(let $arg = arg::<$arg>(&ctx, values.next())?;)*
+                    (self)($($arg),*).map(IntoValue::into_value)
+                })
+            }
+        }
+
+        impl<F, R, $($arg,)*> NativeFn<(WithContext, $($arg,)*)> for F
+        where
+            F: (Fn(Context, $($arg),*) -> Result<R, Error>) + 'static,
+            R: IntoValue,
+            $($arg: FromValue,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_proc(self, doc: Option<Str>) -> Proc {
+                let optional = [$($arg::OPTIONAL),*];
+                let max = optional.len();
+
+                Proc::from_native(parameters(&optional), doc, move |ctx, values| {
+                    check_arity(&ctx, &values, max)?;
+                    let mut values = values.into_iter();
+                    $(let $arg = arg::<$arg>(&ctx, values.next())?;)*
+                    (self)(ctx, $($arg),*).map(IntoValue::into_value)
+                })
+            }
+        }
+    };
+}
+
+impl_native_fn!();
+impl_native_fn!(A1);
+impl_native_fn!(A1, A2);
+impl_native_fn!(A1, A2, A3);
+impl_native_fn!(A1, A2, A3, A4);
+impl_native_fn!(A1, A2, A3, A4, A5);
+impl_native_fn!(A1, A2, A3, A4, A5, A6);
+impl_native_fn!(A1, A2, A3, A4, A5, A6, A7);
+impl_native_fn!(A1, A2, A3, A4, A5, A6, A7, A8);
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use im_rc::vector;
+
+    use crate::{
+        proc::{Callable, Proc},
+        Context, Str, Value,
+    };
+
+    #[test]
+    fn typed() {
+        let ctx = Context::new();
+        let repeat = Proc::from_fn(None, |n: i64, s: Str| {
+            Ok(Str::from(s.as_str().repeat(n as usize)))
+        });
+
+        assert_eq!(repeat.min_arity(), 2);
+        assert_eq!(
+            repeat
+                .call(ctx.clone(), vector![2.into(), "ab".into()])
+                .unwrap(),
+            "abab".into()
+        );
+
+        let err = repeat
+            .call(ctx.clone(), vector!["ab".into(), 2.into()])
+            .unwrap_err();
+        assert_eq!(err.name().as_str(), "wrong-type-arg");
+        assert_eq!(err.args(), Some(vector!["ab".into()]));
+
+        let err = repeat
+            .call(ctx.clone(), vector![1.into(), "ab".into(), 3.into()])
+            .unwrap_err();
+        assert_eq!(err.name().as_str(), "wrong-number-of-args");
+    }
+
+    #[test]
+    fn foreign() {
+        struct Counter(Cell<i64>);
+
+        let ctx = Context::new();
+        let incr = Proc::from_fn(None, |c: Rc<Counter>| {
+            c.0.set(c.0.get() + 1);
+            Ok(c.0.get())
+        });
+
+        let counter = Value::foreign(Counter(Cell::new(0)));
+        assert_eq!(
+            incr.call(ctx.clone(), vector![counter.clone()]).unwrap(),
+            1.into()
+        );
+        assert_eq!(
+            incr.call(ctx.clone(), vector![counter.clone()]).unwrap(),
+            2.into()
+        );
+        assert_eq!(
+            counter.downcast_ref::<Counter>().map(|c| c.0.get()),
+            Some(2)
+        );
+
+        let err = incr.call(ctx, vector![Value::foreign(0)]).unwrap_err();
+        assert_eq!(err.name().as_str(), "wrong-type-arg");
+    }
+
+    #[test]
+    fn optional() {
+        let ctx = Context::new();
+        let add = Proc::from_fn(None, |ctx: Context, a: i64, b: Option<i64>| {
+            a.checked_add(b.unwrap_or(1))
+                .ok_or_else(|| ctx.trace().error("out-of-range", None))
+        });
+
+        assert_eq!(add.min_arity(), 1);
+        assert_eq!(add.call(ctx.clone(), vector![1.into()]).unwrap(), 2.into());
+        assert_eq!(
+            add.call(ctx.clone(), vector![1.into(), 2.into()]).unwrap(),
+            Value::from(3)
+        );
+        assert!(add.call(ctx, vector![i64::MAX.into()]).is_err());
+    }
+}
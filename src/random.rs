@@ -0,0 +1,64 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rug::{rand::RandState, Integer};
+
+/// The state of a random number generator.
+///
+/// Clones share the same state, use [`RandomState::copy`] to get an
+/// independent generator.
+#[derive(Clone)]
+pub struct RandomState(Rc<RefCell<RandState<'static>>>);
+
+impl RandomState {
+    /// A generator with the default seed, always producing the same
+    /// sequence.
+    #[inline]
+    pub fn new() -> Self {
+        Self(Rc::new(RefCell::new(RandState::new())))
+    }
+
+    #[inline]
+    pub fn with_seed(seed: &Integer) -> Self {
+        let me = Self::new();
+        me.seed(seed);
+        me
+    }
+
+    #[inline]
+    pub fn seed(&self, seed: &Integer) {
+        self.0.borrow_mut().seed(seed);
+    }
+
+    /// A generator that starts from the current state of this one but
+    /// advances independently.
+    #[inline]
+    pub fn copy(&self) -> Self {
+        Self(Rc::new(RefCell::new(self.0.borrow().clone())))
+    }
+
+    /// A uniformly distributed integer in `[0, bound)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` isn't positive.
+    #[inline]
+    pub fn below(&self, bound: Integer) -> Integer {
+        bound.random_below(&mut *self.0.borrow_mut())
+    }
+
+    /// Shuffles `items` in place, every permutation being equally likely.
+    pub fn shuffle<T>(&self, items: &mut [T]) {
+        let mut rand = self.0.borrow_mut();
+        for i in (1..items.len()).rev() {
+            let j = Integer::from(i + 1).random_below(&mut *rand);
+            items.swap(i, unsafe { j.to_usize().unwrap_unchecked() });
+        }
+    }
+}
+
+impl Default for RandomState {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,783 @@
+//! Conversion between Rust data structures and [`Value`]s through serde.
+//!
+//! Sequences, tuples and tuple structs become lists, maps and structs become
+//! lists of `(key value)` pairs, unit enum variants become symbols and the
+//! other variants become lists starting with the variant symbol, e.g.
+//! `(Move 1 2)` or `(Rect (width 3) (height 4))`. `None` and `()` become
+//! `#nil`.
+
+use std::fmt;
+
+use ::serde::{
+    de::{self, DeserializeOwned, IntoDeserializer, Unexpected, Visitor},
+    ser::{self, Serialize},
+};
+use im_rc::{vector, vector::ConsumingIter, Vector};
+
+use crate::{Str, Symbol, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    #[inline]
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    #[inline]
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Converts `value` into a lisp [`Value`].
+#[inline]
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value> {
+    value.serialize(Serializer)
+}
+
+/// Converts a lisp [`Value`] into a `T`.
+#[inline]
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T> {
+    T::deserialize(Deserializer(value))
+}
+
+#[inline]
+fn symbol(name: &'static str) -> Value {
+    Value::Symbol(Symbol::Name(name.into()))
+}
+
+/// A serializer producing [`Value`]s.
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_i128(self, v: i128) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_u128(self, v: u128) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_f32(self, v: f32) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Value> {
+        Ok(v.into())
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<Value> {
+        Ok(Str::from(v.to_string()).into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> {
+        Ok(Value::List(v.iter().map(Value::from).collect()))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::Nil)
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::Nil)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        Ok(Value::Nil)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        Ok(symbol(variant))
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        Ok(vector![symbol(variant), value.serialize(self)?].into())
+    }
+
+    #[inline]
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SerializeList> {
+        Ok(SerializeList(Vector::new()))
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _len: usize) -> Result<SerializeList> {
+        Ok(SerializeList(Vector::new()))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<SerializeList> {
+        Ok(SerializeList(Vector::new()))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeList> {
+        Ok(SerializeList(vector![symbol(variant)]))
+    }
+
+    #[inline]
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap> {
+        Ok(SerializeMap {
+            entries: Vector::new(),
+            key: None,
+        })
+    }
+
+    #[inline]
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<SerializeMap> {
+        self.serialize_map(None)
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeMap> {
+        Ok(SerializeMap {
+            entries: vector![symbol(variant)],
+            key: None,
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeList(Vector<Value>);
+
+impl SerializeList {
+    #[inline]
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.0.push_back(value.serialize(Serializer)?);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Value;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value> {
+        Ok(self.0.into())
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Value;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value> {
+        Ok(self.0.into())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Value;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value> {
+        Ok(self.0.into())
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = Value;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value> {
+        Ok(self.0.into())
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeMap {
+    entries: Vector<Value>,
+    key: Option<Value>,
+}
+
+impl SerializeMap {
+    #[inline]
+    fn field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.entries
+            .push_back(vector![symbol(key), value.serialize(Serializer)?].into());
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("map value serialized before its key"))?;
+        self.entries
+            .push_back(vector![key, value.serialize(Serializer)?].into());
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value> {
+        Ok(self.entries.into())
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Value;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value> {
+        Ok(self.entries.into())
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = Value;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value> {
+        Ok(self.entries.into())
+    }
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::Nil => Unexpected::Unit,
+        &Value::Boolean(b) => Unexpected::Bool(b),
+        &Value::Character(c) => Unexpected::Char(c),
+        Value::Integer(i) => match i.to_i64() {
+            Some(i) => Unexpected::Signed(i),
+            None => Unexpected::Other("integer"),
+        },
+        Value::Rational(_) => Unexpected::Other("rational"),
+        &Value::Float(x) => Unexpected::Float(x),
+        Value::BigFloat(_) => Unexpected::Other("big float"),
+        Value::String(s) => Unexpected::Str(s.as_str()),
+        Value::Symbol(_) => Unexpected::Other("symbol"),
+        Value::List(_) => Unexpected::Seq,
+        _ => Unexpected::Other("value"),
+    }
+}
+
+/// A deserializer reading from a [`Value`].
+pub struct Deserializer(pub Value);
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::Nil => visitor.visit_unit(),
+            Value::Boolean(b) => visitor.visit_bool(b),
+            Value::Character(c) => visitor.visit_char(c),
+            Value::Integer(i) => {
+                if let Some(i) = i.to_i64() {
+                    visitor.visit_i64(i)
+                } else if let Some(i) = i.to_u64() {
+                    visitor.visit_u64(i)
+                } else if let Some(i) = i.to_i128() {
+                    visitor.visit_i128(i)
+                } else if let Some(i) = i.to_u128() {
+                    visitor.visit_u128(i)
+                } else {
+                    Err(de::Error::custom(format_args!(
+                        "integer {} out of range",
+                        i
+                    )))
+                }
+            }
+            Value::Float(x) => visitor.visit_f64(x),
+            Value::BigFloat(f) => visitor.visit_f64(f.to_f64()),
+            Value::String(s) => visitor.visit_str(s.as_str()),
+            Value::Symbol(Symbol::Name(s)) => visitor.visit_str(s.as_str()),
+            Value::List(l) => visitor.visit_seq(SeqDeserializer::new(l.into())),
+            ref other => Err(de::Error::invalid_type(unexpected(other), &visitor)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::Nil => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::List(l) => visitor.visit_map(MapDeserializer::new(l.into())),
+            Value::Nil => visitor.visit_map(MapDeserializer::new(Vector::new())),
+            ref other => Err(de::Error::invalid_type(unexpected(other), &visitor)),
+        }
+    }
+
+    #[inline]
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.0 {
+            variant @ (Value::Symbol(_) | Value::String(_)) => visitor.visit_enum(Enum {
+                variant,
+                content: None,
+            }),
+            Value::List(l) => {
+                let mut content: Vector<Value> = l.into();
+                match content.pop_front() {
+                    Some(variant @ Value::Symbol(_)) => visitor.visit_enum(Enum {
+                        variant,
+                        content: Some(content),
+                    }),
+                    _ => Err(de::Error::custom("expected a list starting with a symbol")),
+                }
+            }
+            ref other => Err(de::Error::invalid_type(unexpected(other), &visitor)),
+        }
+    }
+
+    ::serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct identifier
+        ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Deserializer;
+
+    #[inline]
+    fn into_deserializer(self) -> Deserializer {
+        Deserializer(self)
+    }
+}
+
+struct SeqDeserializer {
+    iter: ConsumingIter<Value>,
+    len: usize,
+}
+
+impl SeqDeserializer {
+    #[inline]
+    fn new(items: Vector<Value>) -> Self {
+        Self {
+            len: items.len(),
+            iter: items.into_iter(),
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        match self.iter.next() {
+            Some(value) => {
+                self.len -= 1;
+                seed.deserialize(Deserializer(value)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+struct MapDeserializer {
+    iter: ConsumingIter<Value>,
+    len: usize,
+    value: Option<Value>,
+}
+
+impl MapDeserializer {
+    #[inline]
+    fn new(entries: Vector<Value>) -> Self {
+        Self {
+            len: entries.len(),
+            iter: entries.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let entry = match self.iter.next() {
+            Some(Value::List(entry)) if entry.len() == 2 => entry,
+            Some(_) => return Err(de::Error::custom("expected a (key value) pair")),
+            None => return Ok(None),
+        };
+        self.len -= 1;
+
+        let mut entry: Vector<Value> = entry.into();
+        self.value = entry.pop_back();
+        seed.deserialize(Deserializer(entry.remove(0))).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(Deserializer(value)),
+            None => Err(de::Error::custom("map value requested before its key")),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+struct Enum {
+    variant: Value,
+    content: Option<Vector<Value>>,
+}
+
+impl<'de> de::EnumAccess<'de> for Enum {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer)> {
+        let variant = seed.deserialize(Deserializer(self.variant))?;
+        Ok((variant, VariantDeserializer(self.content)))
+    }
+}
+
+struct VariantDeserializer(Option<Vector<Value>>);
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.0 {
+            None => Ok(()),
+            Some(content) if content.is_empty() => Ok(()),
+            Some(_) => Err(de::Error::invalid_type(
+                Unexpected::TupleVariant,
+                &"unit variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        match self.0 {
+            Some(mut content) if content.len() == 1 => {
+                seed.deserialize(Deserializer(content.remove(0)))
+            }
+            _ => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Some(content) => visitor.visit_seq(SeqDeserializer::new(content)),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.0 {
+            Some(content) => visitor.visit_map(MapDeserializer::new(content)),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ::serde::{Deserialize, Serialize};
+    use im_rc::vector;
+
+    use super::{from_value, to_value};
+    use crate::parser::parse;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(u32),
+        Move(i64, i64),
+        Rect { width: u32, height: u32 },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Drawing {
+        name: String,
+        visible: bool,
+        layer: Option<u8>,
+        shapes: Vec<Shape>,
+    }
+
+    #[test]
+    fn roundtrip() {
+        let drawing = Drawing {
+            name: "test".to_string(),
+            visible: true,
+            layer: None,
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(3),
+                Shape::Move(-1, 2),
+                Shape::Rect {
+                    width: 3,
+                    height: 4,
+                },
+            ],
+        };
+
+        let value = to_value(&drawing).unwrap();
+        assert_eq!(
+            format!("{:?}", value),
+            "((name \"test\") (visible #t) (layer #nil) \
+             (shapes (Empty (Circle 3) (Move -1 2) (Rect (width 3) (height 4)))))"
+        );
+        assert_eq!(from_value::<Drawing>(value).unwrap(), drawing);
+
+        assert_eq!(
+            to_value(&(1.5, 2)).unwrap(),
+            vector![1.5.into(), 2.into()].into()
+        );
+        assert_eq!(
+            to_value(&(-3i16, 3u16)).unwrap(),
+            vector![(-3).into(), 3.into()].into()
+        );
+        assert_eq!(
+            from_value::<(f64, f64)>(vector![1.5.into(), 2.into()].into()).unwrap(),
+            (1.5, 2.0)
+        );
+    }
+
+    #[test]
+    fn from_source() {
+        let code = parse("((Circle 1) Empty (Rect (height 2) (width 1)))").unwrap();
+        let shapes: Vec<Shape> = from_value(code[0].clone()).unwrap();
+        assert_eq!(
+            shapes,
+            vec![
+                Shape::Circle(1),
+                Shape::Empty,
+                Shape::Rect {
+                    width: 1,
+                    height: 2
+                }
+            ]
+        );
+
+        let code = parse("((\"a\" 1) (\"b\" 2))").unwrap();
+        let map: BTreeMap<String, i32> = from_value(code[0].clone()).unwrap();
+        assert_eq!(map.get("b"), Some(&2));
+
+        assert!(from_value::<Vec<u8>>(code[0].clone()).is_err());
+        assert!(from_value::<u8>(300.into()).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use im_rc::{vector, Vector};
 
-use crate::{environment::Bag, eval, Context, Environment, Error, Str, Symbol, Value};
+use crate::{environment::Bag, eval, number, Context, Environment, Error, Str, Symbol, Value};
 
 use std::mem;
 
@@ -22,6 +22,7 @@ pub const NAMES: &[&str] = [
     "letrec*",
     "begin",
     "while",
+    "with-precision",
 ]
 .as_slice();
 
@@ -55,6 +56,7 @@ where
         "letrec" => Some(r#letrec(ctx, env, args, apply_fn)),
         "letrec*" => Some(r#letrec_star_(ctx, env, args, apply_fn)),
         "begin" => Some(begin(ctx, env, args, apply_fn)),
+        "with-precision" => Some(with_precision(ctx, env, args).map(Into::into)),
         _ => None,
     }
 }
@@ -75,7 +77,7 @@ where
 
     let f = args.remove(0);
     if let Value::List(args) = args.remove(0) {
-        apply(f, ctx, args)
+        apply(f, ctx, args.into())
     } else {
         Err(ctx.trace().error("wrong-type-arg", None))
     }
@@ -112,7 +114,7 @@ fn quasiquote(ctx: Context, env: Environment, mut args: Vector<Value>) -> Result
                     return if list.len() == 2 {
                         list.remove(1).eval(ctx, env, false).map(|x| {
                             if let Value::List(l) = x {
-                                Res::Splice(l)
+                                Res::Splice(l.into())
                             } else {
                                 Res::Value(x)
                             }
@@ -215,6 +217,19 @@ fn r#while(ctx: Context, env: Environment, mut args: Vector<Value>) -> Result<Va
     Ok(Value::Unspecified)
 }
 
+fn with_precision(ctx: Context, env: Environment, mut args: Vector<Value>) -> Result<Value, Error> {
+    if args.is_empty() {
+        return Err(ctx.trace().error("syntax-error", None));
+    }
+
+    let precision = match unshift(&mut args).eval(ctx.clone(), env.clone(), false)? {
+        Value::Integer(p) => number::precision(&ctx, p)?,
+        other => return Err(ctx.trace().error("wrong-type-arg", Some(vector![other]))),
+    };
+
+    eval::block(&args, ctx.with_precision(precision), env)
+}
+
 fn set_em_(
     ctx: Context,
     env: Environment,
@@ -1,4 +1,4 @@
-use std::{borrow::Borrow, fmt, hash::Hash, mem};
+use std::{borrow::Borrow, cell::OnceCell, fmt, hash::Hash, mem, rc::Rc};
 
 use ecow::{EcoString, EcoVec};
 
@@ -7,11 +7,57 @@ use crate::{
     Context,
 };
 
+/// How many characters apart the offsets in a [`CharIndex`] are.
+const INDEX_STEP: usize = 64;
+
+/// The byte offsets of every [`INDEX_STEP`]th character of a string, built
+/// the first time a character that far in a non-ASCII string is looked up.
+/// Clones made after that share it.
+#[derive(Clone, Default)]
+struct CharIndex(OnceCell<Rc<[usize]>>);
+
+impl CharIndex {
+    /// The byte offset of character `n` of `s`, which is `len` characters
+    /// long, or `None` if it is past the end of `s`.
+    fn byte_offset(&self, s: &str, len: usize, n: usize) -> Option<usize> {
+        if n > len {
+            return None;
+        }
+        if n == len {
+            return Some(s.len());
+        }
+        if s.len() == len {
+            // Only ASCII characters.
+            return Some(n);
+        }
+
+        let base = if n < INDEX_STEP {
+            0
+        } else {
+            let index = self.0.get_or_init(|| {
+                s.char_indices()
+                    .skip(INDEX_STEP)
+                    .step_by(INDEX_STEP)
+                    .map(|(i, _)| i)
+                    .collect()
+            });
+            index[n / INDEX_STEP - 1]
+        };
+
+        let rest = unsafe { s.get_unchecked(base..) };
+        rest.skip_chars(n % INDEX_STEP)
+            .map(|r| base + rest.len() - r.len())
+    }
+}
+
 #[derive(Clone)]
 enum Repr {
     Empty,
-    Static(&'static str, usize),
-    Alloc(EcoVec<u8>, usize),
+    Static(&'static str, usize, CharIndex),
+    Alloc(EcoVec<u8>, usize, CharIndex),
+    /// A part of a shared buffer: the buffer, the byte offset and byte length
+    /// of the part and its length in characters.
+    Slice(EcoVec<u8>, usize, usize, usize, CharIndex),
 }
 
 #[derive(Clone)]
@@ -19,7 +65,7 @@ pub struct Str(Repr);
 
 enum SubStr<'a> {
     Static(&'static str, usize),
-    Alloc(&'a str, usize),
+    Alloc(&'a EcoVec<u8>, usize, usize, usize),
 }
 
 impl<'a> Borrow<str> for SubStr<'a> {
@@ -27,7 +73,7 @@ impl<'a> Borrow<str> for SubStr<'a> {
     fn borrow(&self) -> &str {
         match self {
             Self::Static(s, _) => s,
-            Self::Alloc(s, _) => s,
+            Self::Alloc(s, start, blen, _) => unsafe { slice_str(s, *start, *blen) },
         }
     }
 }
@@ -40,54 +86,79 @@ impl<'a> Into<Str> for SubStr<'a> {
                 if s.is_empty() {
                     Str(Repr::Empty)
                 } else {
-                    Str(Repr::Static(s, l))
-                }
-            }
-            Self::Alloc(s, l) => {
-                if s.is_empty() {
-                    Str(Repr::Empty)
-                } else {
-                    Str(Repr::Alloc(EcoVec::from(s.as_bytes()), l))
+                    Str(Repr::Static(s, l, CharIndex::default()))
                 }
             }
+            // A copy, so that the string cache doesn't keep the whole buffer
+            // alive.
+            Self::Alloc(s, start, blen, l) => Str(Repr::Alloc(
+                EcoVec::from(unsafe { s.get_unchecked(start..(start + blen)) }),
+                l,
+                CharIndex::default(),
+            )),
         }
     }
 }
 
+/// # Safety
+/// `start..start + blen` must be in bounds of `s` and fall on character
+/// boundaries
+#[inline]
+unsafe fn slice_str(s: &EcoVec<u8>, start: usize, blen: usize) -> &str {
+    std::str::from_utf8_unchecked(s.get_unchecked(start..(start + blen)))
+}
+
 impl Repr {
     pub fn as_str(&self) -> &str {
         match self {
             Self::Empty => "",
-            Self::Static(s, _) => s,
-            Self::Alloc(s, _) => unsafe { std::str::from_utf8_unchecked(s.as_slice()) },
+            Self::Static(s, _, _) => s,
+            Self::Alloc(s, _, _) => unsafe { std::str::from_utf8_unchecked(s.as_slice()) },
+            Self::Slice(s, start, blen, _, _) => unsafe { slice_str(s, *start, *blen) },
         }
     }
 
     pub fn is_empty(&self) -> bool {
         match self {
             Self::Empty => true,
-            Self::Static(s, _) => s.is_empty(),
-            Self::Alloc(s, _) => s.is_empty(),
+            Self::Static(s, _, _) => s.is_empty(),
+            Self::Alloc(s, _, _) => s.is_empty(),
+            Self::Slice(_, _, blen, _, _) => *blen == 0,
         }
     }
 
     pub fn len(&self) -> usize {
         match self {
             Self::Empty => 0,
-            Self::Static(_, l) => *l,
-            Self::Alloc(_, l) => *l,
+            Self::Static(_, l, _) => *l,
+            Self::Alloc(_, l, _) => *l,
+            Self::Slice(_, _, _, l, _) => *l,
         }
     }
 
     pub fn bytes_len(&self) -> usize {
         match self {
             Self::Empty => 0,
-            Self::Static(s, _) => s.len(),
-            Self::Alloc(s, _) => s.len(),
+            Self::Static(s, _, _) => s.len(),
+            Self::Alloc(s, _, _) => s.len(),
+            Self::Slice(_, _, blen, _, _) => *blen,
         }
     }
 
-    fn substr(&self, start: usize, rlen: Option<usize>) -> Option<SubStr> {
+    /// The byte offset of character `n`, or `None` if it is past the end.
+    fn byte_offset(&self, n: usize) -> Option<usize> {
+        match self {
+            Self::Empty => (n == 0).then_some(0),
+            Self::Static(s, l, i) => i.byte_offset(s, *l, n),
+            Self::Alloc(_, l, i) | Self::Slice(_, _, _, l, i) => {
+                i.byte_offset(self.as_str(), *l, n)
+            }
+        }
+    }
+
+    /// The byte offset and byte length of the `rlen` characters starting at
+    /// character `start`, or `None` if they are out of bounds.
+    fn byte_range(&self, start: usize, rlen: Option<usize>) -> Option<(usize, usize, usize)> {
         let len = self.len();
 
         if start > len {
@@ -105,106 +176,92 @@ impl Repr {
             new_len
         };
 
-        if matches!(self, Self::Empty) {
-            return Some(SubStr::Static("", 0));
+        if rlen == 0 {
+            return Some((0, 0, 0));
         }
 
-        let s = unsafe { self.as_str().skip_chars(start).unwrap_unchecked() };
-        let bstart = self.bytes_len() - s.len();
-        let bsize = if new_len == rlen {
-            self.bytes_len() - bstart
-        } else {
-            unsafe { s.skip_chars(rlen).unwrap_unchecked().len() }
-        };
+        let bstart = self.byte_offset(start)?;
+        let bend = self.byte_offset(start + rlen)?;
 
-        match self {
-            Self::Empty => unreachable!(),
-            Self::Static(s, _) => Some(SubStr::Static(&s[bstart..(bstart + bsize)], new_len)),
-            Self::Alloc(ref s, _) => Some(SubStr::Alloc(
-                unsafe { std::str::from_utf8_unchecked(s.get_unchecked(bstart..(bstart + bsize))) },
-                new_len,
-            )),
-        }
+        Some((bstart, bend - bstart, rlen))
     }
 
-    pub fn substring(self, start: usize, rlen: Option<usize>) -> Option<Self> {
-        let len = self.len();
-
-        if start > len {
-            return None;
-        }
-        let new_len = len - start;
-
-        let rlen = if let Some(l) = rlen {
-            if l > new_len {
-                return None;
-            } else {
-                l
-            }
-        } else {
-            new_len
-        };
+    fn substr(&self, start: usize, rlen: Option<usize>) -> Option<SubStr> {
+        let (bstart, bsize, rlen) = self.byte_range(start, rlen)?;
 
-        if matches!(self, Self::Empty) {
-            return Some(Self::Empty);
-        }
+        Some(match self {
+            _ if bsize == 0 => SubStr::Static("", 0),
+            Self::Empty => unreachable!(),
+            Self::Static(s, _, _) => SubStr::Static(&s[bstart..(bstart + bsize)], rlen),
+            Self::Alloc(s, _, _) => SubStr::Alloc(s, bstart, bsize, rlen),
+            Self::Slice(s, offset, _, _, _) => SubStr::Alloc(s, offset + bstart, bsize, rlen),
+        })
+    }
 
-        let s = unsafe { self.as_str().skip_chars(start).unwrap_unchecked() };
-        let bstart = self.bytes_len() - s.len();
-        let bsize = if new_len == rlen {
-            self.bytes_len() - bstart
-        } else {
-            unsafe { s.skip_chars(rlen).unwrap_unchecked().len() }
-        };
+    pub fn substring(self, start: usize, rlen: Option<usize>) -> Option<Self> {
+        let (bstart, bsize, rlen) = self.byte_range(start, rlen)?;
 
-        match self {
+        Some(match self {
+            _ if bsize == 0 => Self::Empty,
+            // The whole string keeps the index it may already have.
+            whole if bsize == whole.bytes_len() => whole,
             Self::Empty => unreachable!(),
-            Self::Static(s, _) => Some(Self::Static(&s[bstart..(bstart + bsize)], new_len)),
-            Self::Alloc(mut s, _) => {
-                _ = s.make_mut();
-                unsafe {
-                    std::ptr::copy_nonoverlapping(
-                        s.as_ptr().add(bstart),
-                        s.as_ptr() as *mut _,
-                        bsize,
-                    )
-                };
-                s.truncate(bsize);
-
-                Some(Self::Alloc(s, rlen))
+            Self::Static(s, _, _) => {
+                Self::Static(&s[bstart..(bstart + bsize)], rlen, CharIndex::default())
             }
-        }
+            Self::Alloc(s, _, _) => Self::Slice(s, bstart, bsize, rlen, CharIndex::default()),
+            Self::Slice(s, offset, _, _, _) => {
+                Self::Slice(s, offset + bstart, bsize, rlen, CharIndex::default())
+            }
+        })
     }
 
     pub fn concat(self, other: Self) -> Self {
         match (self, other) {
             (Self::Empty, other) => other,
             (me, Self::Empty) => me,
-            (Self::Static(ls, ll), Self::Static(rs, rl)) => {
+            (Self::Static(ls, ll, _), Self::Static(rs, rl, _)) => {
                 let mut s = EcoVec::new();
                 s.extend_from_slice(ls.as_bytes());
                 s.extend_from_slice(rs.as_bytes());
-                Self::Alloc(s, ll + rl)
+                Self::Alloc(s, ll + rl, CharIndex::default())
             }
-            (Self::Alloc(mut ls, ll), Self::Static(rs, rl)) => {
+            (Self::Alloc(mut ls, ll, _), Self::Static(rs, rl, _)) => {
                 ls.extend_from_slice(rs.as_bytes());
-                Self::Alloc(ls, ll + rl)
+                Self::Alloc(ls, ll + rl, CharIndex::default())
             }
-            (Self::Static(ls, ll), Self::Alloc(mut rs, rl)) => {
+            (Self::Static(ls, ll, _), Self::Alloc(mut rs, rl, _)) => {
                 rs.extend_from_slice(ls.as_bytes());
                 unsafe { std::slice::from_raw_parts_mut(rs.as_ptr() as *mut u8, rs.len()) }
                     .rotate_right(ls.len());
-                Self::Alloc(rs, ll + rl)
+                Self::Alloc(rs, ll + rl, CharIndex::default())
             }
-            (Repr::Alloc(mut ls, ll), Repr::Alloc(rs, rl)) => {
+            (Repr::Alloc(mut ls, ll, _), Repr::Alloc(rs, rl, _)) => {
                 ls.extend_from_slice(rs.as_slice());
-                Self::Alloc(ls, ll + rl)
+                Self::Alloc(ls, ll + rl, CharIndex::default())
+            }
+            (Repr::Alloc(mut ls, ll, _), other) => {
+                ls.extend_from_slice(other.as_str().as_bytes());
+                Self::Alloc(ls, ll + other.len(), CharIndex::default())
+            }
+            (me, other) => {
+                let mut s = EcoVec::with_capacity(me.bytes_len() + other.bytes_len());
+                s.extend_from_slice(me.as_str().as_bytes());
+                s.extend_from_slice(other.as_str().as_bytes());
+                Self::Alloc(s, me.len() + other.len(), CharIndex::default())
             }
         }
     }
 
     pub fn char_at(&self, n: usize) -> Option<char> {
-        self.as_str().skip_chars(n).and_then(|s| s.chars().next())
+        if n >= self.len() {
+            return None;
+        }
+
+        let offset = self.byte_offset(n)?;
+        unsafe { self.as_str().get_unchecked(offset..) }
+            .chars()
+            .next()
     }
 }
 
@@ -212,10 +269,8 @@ impl fmt::Debug for Repr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Empty => write!(f, "\"\""),
-            Self::Static(s, _) => fmt::Debug::fmt(s, f),
-            Self::Alloc(s, _) => {
-                fmt::Debug::fmt(unsafe { std::str::from_utf8_unchecked(s.as_slice()) }, f)
-            }
+            Self::Static(s, _, _) => fmt::Debug::fmt(s, f),
+            Self::Alloc(..) | Self::Slice(..) => fmt::Debug::fmt(self.as_str(), f),
         }
     }
 }
@@ -224,10 +279,8 @@ impl fmt::Display for Repr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Empty => Ok(()),
-            Self::Static(s, _) => fmt::Display::fmt(s, f),
-            Self::Alloc(s, _) => {
-                fmt::Display::fmt(unsafe { std::str::from_utf8_unchecked(s.as_slice()) }, f)
-            }
+            Self::Static(s, _, _) => fmt::Display::fmt(s, f),
+            Self::Alloc(..) | Self::Slice(..) => fmt::Display::fmt(self.as_str(), f),
         }
     }
 }
@@ -238,7 +291,7 @@ impl From<&'static str> for Repr {
         if value.is_empty() {
             Self::Empty
         } else {
-            Self::Static(value, value.count_chars())
+            Self::Static(value, value.count_chars(), CharIndex::default())
         }
     }
 }
@@ -250,7 +303,7 @@ impl From<EcoVec<u8>> for Repr {
             Self::Empty
         } else {
             let len = value.count_chars();
-            Self::Alloc(value, len)
+            Self::Alloc(value, len, CharIndex::default())
         }
     }
 }
@@ -387,7 +440,7 @@ impl Str {
     /// we need to check first that `raw` is a valid utf-8 encoded string and
     /// its length is `len`
     pub unsafe fn from_raw(raw: EcoVec<u8>, len: usize) -> Self {
-        Self(Repr::Alloc(raw, len))
+        Self(Repr::Alloc(raw, len, CharIndex::default()))
     }
 
     #[inline]
@@ -397,6 +450,9 @@ impl Str {
         inner.substring(start, len).map(Self)
     }
 
+    /// Like [`Str::substring`], but the result is interned in the string
+    /// cache of `ctx`. Unlike [`Str::substring`] it doesn't share the buffer
+    /// of `self`.
     pub fn substring_in_context(
         &self,
         ctx: &mut Context,
@@ -448,7 +504,7 @@ impl From<String> for Str {
     fn from(value: String) -> Self {
         let raw = EcoVec::from(value.as_bytes());
         let len = raw.count_chars();
-        Self(Repr::Alloc(raw, len))
+        Self(Repr::Alloc(raw, len, CharIndex::default()))
     }
 }
 
@@ -536,3 +592,145 @@ impl Borrow<str> for Str {
         self.as_str()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+        rc::Rc,
+    };
+
+    use super::{Repr, Str};
+    use crate::Context;
+
+    fn hash(s: &Str) -> u64 {
+        let mut h = DefaultHasher::new();
+        s.hash(&mut h);
+        h.finish()
+    }
+
+    fn shares(parent: &Str, s: &Str) -> bool {
+        let range = parent.as_str().as_bytes().as_ptr_range();
+        range.contains(&s.as_str().as_ptr())
+    }
+
+    #[test]
+    fn substrings_share_buffer() {
+        let parent = Str::from(String::from("añbλcd"));
+
+        let sub = parent.clone().substring(1, Some(3)).unwrap();
+        assert!(matches!(sub.repr(), Repr::Slice(..)));
+        assert!(shares(&parent, &sub));
+        assert_eq!(sub.as_str(), "ñbλ");
+        assert_eq!(sub.len(), 3);
+        assert_eq!(sub.bytes_len(), 5);
+        assert_eq!(sub.char_at(2), Some('λ'));
+
+        let owned = Str::from(String::from("ñbλ"));
+        assert_eq!(sub, owned);
+        assert_eq!(hash(&sub), hash(&owned));
+        assert_eq!(sub.cmp(&owned), std::cmp::Ordering::Equal);
+
+        let subsub = sub.clone().substring(2, None).unwrap();
+        assert!(shares(&parent, &subsub));
+        assert_eq!(subsub.as_str(), "λ");
+        assert_eq!(subsub.len(), 1);
+
+        assert!(parent.clone().substring(6, None).unwrap().is_empty());
+        assert!(parent.clone().substring(2, Some(5)).is_none());
+        assert!(matches!(
+            parent.clone().substring(0, None).unwrap().repr(),
+            Repr::Alloc(..)
+        ));
+    }
+
+    #[test]
+    fn interned_substrings_release_buffer() {
+        let mut parent = Str::from(String::from("añbλcd"));
+
+        let mut ctx = Context::default();
+        let sub = parent.substring_in_context(&mut ctx, 2, Some(2)).unwrap();
+        assert!(!shares(&parent, &sub));
+        assert_eq!(sub.as_str(), "bλ");
+        assert_eq!(sub.len(), 2);
+        drop(sub);
+
+        // The copy in the cache doesn't keep the buffer of `parent` alive.
+        match parent.repr_mut() {
+            Repr::Alloc(buf, _, _) => assert!(buf.is_unique()),
+            _ => unreachable!(),
+        }
+        assert_eq!(ctx.make_string("bλ").as_str(), "bλ");
+    }
+
+    #[test]
+    fn static_substrings() {
+        let s = Str::from("abcdef");
+        let sub = s.clone().substring(1, Some(2)).unwrap();
+        assert_eq!(sub.as_str(), "bc");
+        assert_eq!(sub.len(), 2);
+        assert!(shares(&s, &sub));
+    }
+
+    #[test]
+    fn char_index() {
+        let text: String = (0..1000)
+            .map(|i| ['a', 'ñ', 'λ', '😀', 'b'][i % 5])
+            .collect();
+        let chars: Vec<char> = text.chars().collect();
+        let s = Str::from(text.clone());
+
+        for (i, c) in chars.iter().enumerate() {
+            assert_eq!(s.char_at(i), Some(*c));
+        }
+        assert_eq!(s.char_at(chars.len()), None);
+
+        let index = |s: &Str| match s.repr() {
+            Repr::Alloc(_, _, index) => index.0.get().cloned(),
+            _ => unreachable!(),
+        };
+        let clone = s.clone();
+        let whole = s.clone().substring(0, None).unwrap();
+        assert!(Rc::ptr_eq(&index(&s).unwrap(), &index(&clone).unwrap()));
+        assert!(Rc::ptr_eq(&index(&s).unwrap(), &index(&whole).unwrap()));
+
+        let long_ascii = Str::from("a".repeat(1000));
+        assert_eq!(long_ascii.char_at(900), Some('a'));
+        assert!(index(&long_ascii).is_none());
+
+        for (start, len) in [(0, 64), (63, 2), (64, 64), (100, 500), (999, 1), (1000, 0)] {
+            let expected: String = chars[start..(start + len)].iter().collect();
+            let sub = s.clone().substring(start, Some(len)).unwrap();
+            assert_eq!(sub.as_str(), expected);
+            assert_eq!(sub.len(), len);
+            if len > 0 {
+                assert_eq!(sub.char_at(len - 1), chars.get(start + len - 1).copied());
+            }
+        }
+
+        let ascii = Str::from("abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(ascii.char_at(25), Some('z'));
+        assert_eq!(ascii.char_at(26), None);
+        assert_eq!(Str::from("").char_at(0), None);
+    }
+
+    #[test]
+    fn concat_slices() {
+        let parent = Str::from(String::from("hello world"));
+        let mut hello = parent.clone().substring(0, Some(5)).unwrap();
+        let world = parent.clone().substring(6, None).unwrap();
+
+        hello.concat(Str::from(", "));
+        hello.concat(world.clone());
+        assert_eq!(hello.as_str(), "hello, world");
+        assert_eq!(hello.len(), 12);
+        assert_eq!(parent.as_str(), "hello world");
+        assert_eq!(world.as_str(), "world");
+
+        let mut world2 = world.clone();
+        world2.concat(Str::from(String::from("!")));
+        assert_eq!(world2.as_str(), "world!");
+        assert_eq!(world2.len(), 6);
+    }
+}
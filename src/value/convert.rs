@@ -0,0 +1,217 @@
+use std::{any::Any, rc::Rc};
+
+use rug::{Float, Integer, Rational};
+
+use crate::{Foreign, Str, Value};
+
+/// Conversion from a lisp value to a Rust type.
+///
+/// On failure the value that couldn't be converted is given back, so that
+/// it can be reported in a `wrong-type-arg` error.
+pub trait FromValue: Sized {
+    /// Whether a missing argument of this type can be left out of a call to
+    /// a typed native procedure, see [`NativeFn`](crate::proc::NativeFn).
+    const OPTIONAL: bool = false;
+
+    fn from_value(value: Value) -> Result<Self, Value>;
+}
+
+/// Conversion from a Rust type to a lisp value, implemented for everything
+/// that can be turned into a [`Value`].
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+impl<T: Into<Value>> IntoValue for T {
+    #[inline]
+    fn into_value(self) -> Value {
+        self.into()
+    }
+}
+
+impl FromValue for Value {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        Ok(value)
+    }
+}
+
+impl FromValue for Integer {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Integer(i) => Ok(i),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Integers are converted to rationals with a denominator of 1.
+impl FromValue for Rational {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Integer(i) => Ok(i.into()),
+            Value::Rational(r) => Ok(r),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Exact numbers are rounded to the nearest float.
+impl FromValue for f64 {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Integer(ref i) => Ok(i.to_f64()),
+            Value::Rational(ref r) => Ok(r.to_f64()),
+            Value::Float(x) => Ok(x),
+            Value::BigFloat(ref f) => Ok(f.to_f64()),
+            _ => Err(value),
+        }
+    }
+}
+
+impl FromValue for Float {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::BigFloat(f) => Ok(f),
+            _ => Err(value),
+        }
+    }
+}
+
+macro_rules! impl_int_from {
+    ($($ty:ident => $to:ident),+ $(,)?) => {
+        $(
+            impl FromValue for $ty {
+                #[inline]
+                fn from_value(value: Value) -> Result<Self, Value> {
+                    match value {
+                        Value::Integer(ref i) => i.$to().ok_or(value),
+                        _ => Err(value),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_int_from! {
+    i64 => to_i64,
+    usize => to_usize,
+}
+
+impl FromValue for Str {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::String(s) => Ok(s),
+            _ => Err(value),
+        }
+    }
+}
+
+impl FromValue for String {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::String(s) => Ok(s.as_str().to_string()),
+            _ => Err(value),
+        }
+    }
+}
+
+impl FromValue for char {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Character(c) => Ok(c),
+            _ => Err(value),
+        }
+    }
+}
+
+impl FromValue for bool {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Boolean(b) => Ok(b),
+            _ => Err(value),
+        }
+    }
+}
+
+impl FromValue for Foreign {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Foreign(f) => Ok(f),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Succeeds for foreign values holding a `T`.
+impl<T: Any> FromValue for Rc<T> {
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        value.downcast().ok_or(value)
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::List(l) => l.into_iter().map(T::from_value).collect(),
+            _ => Err(value),
+        }
+    }
+}
+
+/// `#nil` converts to `None`, anything else is converted to `T`.
+impl<T: FromValue> FromValue for Option<T> {
+    const OPTIONAL: bool = true;
+
+    #[inline]
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Nil => Ok(None),
+            _ => T::from_value(value).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use im_rc::vector;
+    use rug::Integer;
+
+    use super::FromValue;
+    use crate::{Str, Value};
+
+    #[test]
+    fn from_value() {
+        assert_eq!(i64::from_value(42.into()), Ok(42));
+        assert_eq!(usize::from_value((-1).into()), Err((-1).into()));
+        assert_eq!(Integer::from_value(1.into()), Ok(Integer::from(1)));
+        assert_eq!(f64::from_value(1.5.into()), Ok(1.5));
+        assert_eq!(f64::from_value(2.into()), Ok(2.0));
+        assert_eq!(Str::from_value("ciao".into()), Ok(Str::from("ciao")));
+        assert_eq!(String::from_value("ciao".into()), Ok("ciao".to_string()));
+        assert_eq!(char::from_value('x'.into()), Ok('x'));
+        assert_eq!(bool::from_value(true.into()), Ok(true));
+        assert_eq!(bool::from_value(Value::Nil), Err(Value::Nil));
+        assert_eq!(Option::<i64>::from_value(Value::Nil), Ok(None));
+        assert_eq!(Option::<i64>::from_value(1.into()), Ok(Some(1)));
+        assert_eq!(
+            Vec::<i64>::from_value(vector![1.into(), 2.into()].into()),
+            Ok(vec![1, 2])
+        );
+        assert_eq!(
+            Vec::<i64>::from_value(vector![1.into(), "2".into()].into()),
+            Err("2".into())
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::{any::Any, fmt, rc::Rc};
+
+/// An opaque Rust object carried around by lisp code.
+///
+/// Two foreign values are equal only if they are the same object.
+#[derive(Clone)]
+pub struct Foreign {
+    value: Rc<dyn Any>,
+    type_name: &'static str,
+}
+
+/// The name of `T` without its module path, e.g. `Config` for
+/// `my_crate::config::Config`.
+fn short_type_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let end = name.find('<').unwrap_or(name.len());
+
+    match name[..end].rfind("::") {
+        Some(i) => &name[i + 2..end],
+        None => &name[..end],
+    }
+}
+
+impl Foreign {
+    #[inline]
+    pub fn new<T: Any>(value: T) -> Self {
+        Self::from_rc(Rc::new(value))
+    }
+
+    #[inline]
+    pub fn from_rc<T: Any>(value: Rc<T>) -> Self {
+        Self::with_type_name(value, short_type_name::<T>())
+    }
+
+    #[inline]
+    pub fn with_type_name<T: Any>(value: Rc<T>, type_name: &'static str) -> Self {
+        Self { value, type_name }
+    }
+
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    #[inline]
+    pub fn is<T: Any>(&self) -> bool {
+        self.value.is::<T>()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    #[inline]
+    pub fn downcast<T: Any>(&self) -> Option<Rc<T>> {
+        Rc::clone(&self.value).downcast().ok()
+    }
+
+    #[inline]
+    fn addr(&self) -> usize {
+        Rc::as_ptr(&self.value) as *const u8 as usize
+    }
+}
+
+impl PartialEq for Foreign {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+impl Eq for Foreign {}
+
+impl fmt::Debug for Foreign {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#<foreign {}>", self.type_name)
+    }
+}
+
+impl fmt::Display for Foreign {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::Foreign;
+
+    struct Config {
+        name: &'static str,
+    }
+
+    #[test]
+    fn foreign() {
+        let a = Foreign::new(Config { name: "test" });
+        let b = Foreign::new(Config { name: "test" });
+
+        assert_eq!(format!("{:?}", a), "#<foreign Config>");
+        assert_eq!(a, a.clone());
+        assert_ne!(a, b);
+
+        assert!(a.is::<Config>());
+        assert!(!a.is::<String>());
+        assert_eq!(a.downcast_ref::<Config>().map(|c| c.name), Some("test"));
+        assert!(a.downcast::<String>().is_none());
+
+        let rc = a.downcast::<Config>().unwrap();
+        assert_eq!(Foreign::from_rc(rc), a);
+
+        let v = Foreign::new(Vec::<Config>::new());
+        assert_eq!(v.type_name(), "Vec");
+        let h = Foreign::with_type_name(Rc::new(1), "handle");
+        assert_eq!(format!("{:?}", h), "#<foreign handle>");
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+use im_rc::{vector, Vector};
+
+use crate::{parser::SourceSpan, Value};
+
+/// A list of values that remembers where it has been read from, if it comes
+/// from source code.
+///
+/// The span doesn't take part in comparisons, two lists are equal if their
+/// elements are.
+#[derive(Clone, Default)]
+pub struct List {
+    items: Vector<Value>,
+    span: Option<Rc<SourceSpan>>,
+}
+
+impl List {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_span(items: Vector<Value>, span: Rc<SourceSpan>) -> Self {
+        Self {
+            items,
+            span: Some(span),
+        }
+    }
+
+    #[inline]
+    pub fn span(&self) -> Option<Rc<SourceSpan>> {
+        self.span.clone()
+    }
+
+    #[inline]
+    pub fn set_span(&mut self, span: Option<Rc<SourceSpan>>) {
+        self.span = span;
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Vector<Value> {
+        self.items
+    }
+}
+
+impl Deref for List {
+    type Target = Vector<Value>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl DerefMut for List {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.items
+    }
+}
+
+impl PartialEq for List {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+    }
+}
+
+impl Eq for List {}
+
+impl From<Vector<Value>> for List {
+    #[inline]
+    fn from(items: Vector<Value>) -> Self {
+        Self { items, span: None }
+    }
+}
+
+impl From<List> for Vector<Value> {
+    #[inline]
+    fn from(value: List) -> Self {
+        value.items
+    }
+}
+
+impl FromIterator<Value> for List {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = Value>>(iter: T) -> Self {
+        Vector::from_iter(iter).into()
+    }
+}
+
+impl IntoIterator for List {
+    type Item = Value;
+
+    type IntoIter = vector::ConsumingIter<Value>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a List {
+    type Item = &'a Value;
+
+    type IntoIter = vector::Iter<'a, Value>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl fmt::Debug for List {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.items, f)
+    }
+}
@@ -46,8 +46,22 @@ fn _expand(me: Value, ctx: Context, env: Environment) -> Result<Expanded1, Error
         return Ok(Continue((Value::List(l), false)));
     };
 
+    // Errors raised by the macro point to the call site, and so does the
+    // expansion unless the macro already gave it a position.
+    let span = l.span();
+    let ctx = match span {
+        Some(ref span) => ctx.with_span(span.clone()),
+        None => ctx,
+    };
+
     l.remove(0);
-    r#macro.call(ctx, l).map(|x| Continue((x, true)))
+    r#macro.call(ctx, l.into()).map(|x| match x {
+        Value::List(mut x) if x.span().is_none() => {
+            x.set_span(span);
+            Continue((Value::List(x), true))
+        }
+        x => Continue((x, true)),
+    })
 }
 
 fn expand_quasiquote(me: Value, ctx: Context, env: Environment) -> Result<Value, Error> {
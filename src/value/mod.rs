@@ -1,17 +1,26 @@
+mod convert;
+mod foreign;
+mod list;
 mod macroexpand;
 
-use std::fmt;
+use std::{any::Any, fmt, rc::Rc};
 
 use im_rc::Vector;
-use rug::Integer;
+use rug::{Float, Integer, Rational};
 
 use crate::{
     eval,
+    number::{fmt_bigfloat, fmt_float},
+    parser::SourceSpan,
     proc::UnboundProc,
     util::{print_list_debug, print_list_display},
     BackTrace, Context, Environment, Error, Proc, Str, Symbol, TraceFrame, Var,
 };
 
+pub use convert::{FromValue, IntoValue};
+pub use foreign::Foreign;
+pub use list::List;
+
 #[derive(Clone)]
 pub enum Value {
     Unspecified,
@@ -19,18 +28,22 @@ pub enum Value {
     Boolean(bool),
     Character(char),
     Integer(Integer),
+    Rational(Rational),
+    Float(f64),
+    BigFloat(Float),
     String(Str),
     Symbol(Symbol),
     Fn(Proc),
     Macro(Proc),
     UnboundFn(UnboundProc),
     UnboundMacro(UnboundProc),
-    List(Vector<Value>),
+    List(List),
     Var(Var),
     Environment(Environment),
     Error(Error),
     BackTrace(BackTrace),
     Frame(TraceFrame),
+    Foreign(Foreign),
 }
 
 impl Value {
@@ -56,7 +69,22 @@ impl Value {
 
     #[inline]
     pub fn is_integer(&self) -> bool {
-        matches!(self, Value::Character(_))
+        matches!(self, Value::Integer(_))
+    }
+
+    #[inline]
+    pub fn is_rational(&self) -> bool {
+        matches!(self, Value::Rational(_))
+    }
+
+    #[inline]
+    pub fn is_float(&self) -> bool {
+        matches!(self, Value::Float(_))
+    }
+
+    #[inline]
+    pub fn is_bigfloat(&self) -> bool {
+        matches!(self, Value::BigFloat(_))
     }
 
     #[inline]
@@ -109,6 +137,36 @@ impl Value {
         matches!(self, Value::Frame(_))
     }
 
+    #[inline]
+    pub fn is_foreign(&self) -> bool {
+        matches!(self, Value::Foreign(_))
+    }
+
+    /// Wraps a Rust object into a foreign value.
+    #[inline]
+    pub fn foreign<T: Any>(value: T) -> Self {
+        Self::Foreign(Foreign::new(value))
+    }
+
+    /// Returns a reference to the wrapped object if this is a foreign value
+    /// holding a `T`.
+    #[inline]
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match self {
+            Self::Foreign(f) => f.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the wrapped object if this is a foreign value holding a `T`.
+    #[inline]
+    pub fn downcast<T: Any>(&self) -> Option<Rc<T>> {
+        match self {
+            Self::Foreign(f) => f.downcast(),
+            _ => None,
+        }
+    }
+
     pub fn element_at(&self, ctx: Context, i: &Integer) -> Result<Value, Error> {
         if let Some(i) = i.to_usize() {
             match self {
@@ -122,6 +180,16 @@ impl Value {
         }
     }
 
+    /// Where this value has been read from, if it's a list that comes from
+    /// source code.
+    #[inline]
+    pub fn span(&self) -> Option<Rc<SourceSpan>> {
+        match self {
+            Self::List(l) => l.span(),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn eval(self, ctx: Context, env: Environment, in_block: bool) -> Result<Value, Error> {
         eval::value(self, ctx, env, in_block)
@@ -150,6 +218,9 @@ impl PartialEq for Value {
             (Self::Boolean(l0), Self::Boolean(r0)) => l0 == r0,
             (Self::Character(l0), Self::Character(r0)) => l0 == r0,
             (Self::Integer(l0), Self::Integer(r0)) => l0 == r0,
+            (Self::Rational(l0), Self::Rational(r0)) => l0 == r0,
+            (Self::Float(l0), Self::Float(r0)) => l0.to_bits() == r0.to_bits(),
+            (Self::BigFloat(l0), Self::BigFloat(r0)) => l0.total_cmp(r0).is_eq(),
             (Self::String(l0), Self::String(r0)) => l0 == r0,
             (Self::Symbol(l0), Self::Symbol(r0)) => l0 == r0,
             (Self::Fn(l0), Self::Fn(r0)) => l0 == r0,
@@ -161,6 +232,7 @@ impl PartialEq for Value {
             (Self::Environment(l0), Self::Environment(r0)) => l0 == r0,
             (Self::BackTrace(l0), Self::BackTrace(r0)) => l0 == r0,
             (Self::Frame(l0), Self::Frame(r0)) => l0 == r0,
+            (Self::Foreign(l0), Self::Foreign(r0)) => l0 == r0,
             _ => false,
         }
     }
@@ -189,6 +261,39 @@ impl From<Integer> for Value {
     }
 }
 
+/// Rationals with a denominator of 1 become integers.
+impl From<Rational> for Value {
+    #[inline]
+    fn from(value: Rational) -> Self {
+        if *value.denom() == 1 {
+            Self::Integer(value.into_numer_denom().0)
+        } else {
+            Self::Rational(value)
+        }
+    }
+}
+
+impl From<f64> for Value {
+    #[inline]
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<Float> for Value {
+    #[inline]
+    fn from(value: Float) -> Self {
+        Self::BigFloat(value)
+    }
+}
+
+impl From<f32> for Value {
+    #[inline]
+    fn from(value: f32) -> Self {
+        Self::Float(value.into())
+    }
+}
+
 macro_rules! impl_int_into {
     ($($ty:ty),+ $(,)?) => {
         $(
@@ -218,6 +323,7 @@ macro_rules! impl_int_into {
 
 impl_int_into! {
     i8, u8,
+    i16, u16,
     i32, u32,
     i64, u64,
     i128, u128,
@@ -231,6 +337,13 @@ impl From<&'static str> for Value {
     }
 }
 
+impl From<String> for Value {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::String(value.into())
+    }
+}
+
 impl From<Str> for Value {
     #[inline]
     fn from(value: Str) -> Self {
@@ -248,6 +361,13 @@ impl From<Symbol> for Value {
 impl From<Vector<Value>> for Value {
     #[inline]
     fn from(value: Vector<Value>) -> Self {
+        Self::List(value.into())
+    }
+}
+
+impl From<List> for Value {
+    #[inline]
+    fn from(value: List) -> Self {
         Self::List(value)
     }
 }
@@ -335,6 +455,13 @@ impl From<TraceFrame> for Value {
     }
 }
 
+impl From<Foreign> for Value {
+    #[inline]
+    fn from(value: Foreign) -> Self {
+        Self::Foreign(value)
+    }
+}
+
 impl fmt::Debug for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -349,6 +476,9 @@ impl fmt::Debug for Value {
             }
             Self::Character(c) => fmt::Debug::fmt(c, f),
             Self::Integer(i) => fmt::Debug::fmt(i, f),
+            Self::Rational(r) => fmt::Debug::fmt(r, f),
+            &Self::Float(x) => fmt_float(x, f),
+            Self::BigFloat(x) => fmt_bigfloat(x, f),
             Self::String(s) => fmt::Debug::fmt(s, f),
             Self::Symbol(s) => fmt::Debug::fmt(s, f),
             Self::Fn(p) => p.fmt(f, "fn"),
@@ -361,6 +491,7 @@ impl fmt::Debug for Value {
             Self::Error(e) => fmt::Debug::fmt(e, f),
             Self::BackTrace(b) => fmt::Debug::fmt(b, f),
             Self::Frame(ff) => fmt::Debug::fmt(ff, f),
+            Self::Foreign(ff) => fmt::Debug::fmt(ff, f),
         }
     }
 }
@@ -379,6 +510,9 @@ impl fmt::Display for Value {
             }
             Self::Character(c) => fmt::Display::fmt(c, f),
             Self::Integer(i) => fmt::Display::fmt(i, f),
+            Self::Rational(r) => fmt::Display::fmt(r, f),
+            &Self::Float(x) => fmt_float(x, f),
+            Self::BigFloat(x) => fmt_bigfloat(x, f),
             Self::String(s) => fmt::Display::fmt(s, f),
             Self::Symbol(s) => fmt::Display::fmt(s, f),
             Self::Fn(p) => p.fmt(f, "fn"),
@@ -391,6 +525,7 @@ impl fmt::Display for Value {
             Self::Error(v) => fmt::Display::fmt(v, f),
             Self::BackTrace(b) => fmt::Debug::fmt(b, f),
             Self::Frame(ff) => fmt::Debug::fmt(ff, f),
+            Self::Foreign(ff) => fmt::Debug::fmt(ff, f),
         }
     }
 }
@@ -415,6 +550,10 @@ mod tests {
         assert_eq!(format!("{:?}", Value::Nil), "#nil");
         assert_eq!(format!("{:?}", Value::from(true)), "#t");
         assert_eq!(format!("{:?}", Value::from(1)), "1");
+        assert_eq!(format!("{:?}", Value::from(1.0)), "1.0");
+        assert_eq!(format!("{:?}", Value::from(-1.5e300)), "-1.5e300");
+        assert_eq!(format!("{:?}", Value::from(f64::NAN)), "+nan.0");
+        assert_eq!(format!("{:?}", Value::from(f64::NEG_INFINITY)), "-inf.0");
         assert_eq!(format!("{:?}", Value::from(Some(1))), "1");
         assert_eq!(format!("{:?}", Value::from(Option::<bool>::None)), "#nil");
     }